@@ -17,13 +17,15 @@ use self::Sign::{Minus, NoSign, Plus};
 
 use crate::big_digit::BigDigit;
 use crate::biguint::to_str_radix_reversed;
-use crate::biguint::{BigUint, IntDigits};
+use crate::biguint::{add_mul, add_mul_digit, sub_mul, sub_mul_digit};
+use crate::biguint::{BigUint, IntDigits, U32Digits, U64Digits};
 
 mod addition;
 mod division;
 mod multiplication;
 mod subtraction;
 
+mod bits;
 mod convert;
 mod power;
 mod shift;
@@ -449,7 +451,7 @@ impl Integer for BigInt {
         let lcm = if egcd.gcd.is_zero() {
             Self::ZERO
         } else {
-            BigInt::from(&self.data / &egcd.gcd.data * &other.data)
+            BigInt::from(self.data.div_exact(&egcd.gcd.data) * &other.data)
         };
         (egcd, lcm)
     }
@@ -530,17 +532,17 @@ impl IntDigits for BigInt {
     fn digits(&self) -> &[BigDigit] {
         self.data.digits()
     }
-    // #[inline]
-    // fn digits_mut(&mut self) -> &mut Vec<BigDigit> {
-    //     self.data.digits_mut()
-    // }
-    // #[inline]
-    // fn normalize(&mut self) {
-    //     self.data.normalize();
-    //     if self.data.is_zero() {
-    //         self.sign = NoSign;
-    //     }
-    // }
+    #[inline]
+    fn digits_mut(&mut self) -> &mut Vec<BigDigit> {
+        self.data.digits_mut()
+    }
+    #[inline]
+    fn normalize(&mut self) {
+        self.data.normalize();
+        if self.data.is_zero() {
+            self.sign = NoSign;
+        }
+    }
     #[inline]
     fn capacity(&self) -> usize {
         self.data.capacity()
@@ -609,6 +611,28 @@ impl BigInt {
         }
     }
 
+    /// Creates and initializes a [`BigInt`] from `u64` digits.
+    ///
+    /// The base 2<sup>64</sup> digits are ordered least significant digit first. On 64-bit
+    /// targets the vector becomes the digits of the magnitude without copying.
+    #[inline]
+    pub fn from_u64_digits(sign: Sign, digits: Vec<u64>) -> BigInt {
+        BigInt::from_biguint(sign, BigUint::from_u64_digits(digits))
+    }
+
+    /// Reinitializes a [`BigInt`] from `u64` digits.
+    ///
+    /// The base 2<sup>64</sup> digits are ordered least significant digit first.
+    #[inline]
+    pub fn assign_from_u64_slice(&mut self, sign: Sign, slice: &[u64]) {
+        if sign == NoSign {
+            self.set_zero();
+        } else {
+            self.data.assign_from_u64_slice(slice);
+            self.sign = if self.data.is_zero() { NoSign } else { sign };
+        }
+    }
+
     /// Creates and initializes a [`BigInt`].
     ///
     /// The bytes are in big-endian byte order.
@@ -752,7 +776,7 @@ impl BigInt {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::{BigInt, Sign};
+    /// use rust_monty_parallel::{BigInt, Sign};
     ///
     /// assert_eq!(BigInt::from(-1125).to_u32_digits(), (Sign::Minus, vec![1125]));
     /// assert_eq!(BigInt::from(4294967295u32).to_u32_digits(), (Sign::Plus, vec![4294967295]));
@@ -760,10 +784,10 @@ impl BigInt {
     /// assert_eq!(BigInt::from(-112500000000i64).to_u32_digits(), (Sign::Minus, vec![830850304, 26]));
     /// assert_eq!(BigInt::from(112500000000i64).to_u32_digits(), (Sign::Plus, vec![830850304, 26]));
     /// ```
-    // #[inline]
-    // pub fn to_u32_digits(&self) -> (Sign, Vec<u32>) {
-    //     (self.sign, self.data.to_u32_digits())
-    // }
+    #[inline]
+    pub fn to_u32_digits(&self) -> (Sign, Vec<u32>) {
+        (self.sign, self.data.to_u32_digits())
+    }
 
     /// Returns the sign and the `u64` digits representation of the [`BigInt`] ordered least
     /// significant digit first.
@@ -771,7 +795,7 @@ impl BigInt {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::{BigInt, Sign};
+    /// use rust_monty_parallel::{BigInt, Sign};
     ///
     /// assert_eq!(BigInt::from(-1125).to_u64_digits(), (Sign::Minus, vec![1125]));
     /// assert_eq!(BigInt::from(4294967295u32).to_u64_digits(), (Sign::Plus, vec![4294967295]));
@@ -780,10 +804,10 @@ impl BigInt {
     /// assert_eq!(BigInt::from(112500000000i64).to_u64_digits(), (Sign::Plus, vec![112500000000]));
     /// assert_eq!(BigInt::from(1u128 << 64).to_u64_digits(), (Sign::Plus, vec![0, 1]));
     /// ```
-    // #[inline]
-    // pub fn to_u64_digits(&self) -> (Sign, Vec<u64>) {
-    //     (self.sign, self.data.to_u64_digits())
-    // }
+    #[inline]
+    pub fn to_u64_digits(&self) -> (Sign, Vec<u64>) {
+        (self.sign, self.data.to_u64_digits())
+    }
 
     /// Returns an iterator of `u32` digits representation of the [`BigInt`] ordered least
     /// significant digit first.
@@ -791,7 +815,7 @@ impl BigInt {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigInt;
+    /// use rust_monty_parallel::BigInt;
     ///
     /// assert_eq!(BigInt::from(-1125).iter_u32_digits().collect::<Vec<u32>>(), vec![1125]);
     /// assert_eq!(BigInt::from(4294967295u32).iter_u32_digits().collect::<Vec<u32>>(), vec![4294967295]);
@@ -799,10 +823,10 @@ impl BigInt {
     /// assert_eq!(BigInt::from(-112500000000i64).iter_u32_digits().collect::<Vec<u32>>(), vec![830850304, 26]);
     /// assert_eq!(BigInt::from(112500000000i64).iter_u32_digits().collect::<Vec<u32>>(), vec![830850304, 26]);
     /// ```
-    // #[inline]
-    // pub fn iter_u32_digits(&self) -> U32Digits<'_> {
-    //     self.data.iter_u32_digits()
-    // }
+    #[inline]
+    pub fn iter_u32_digits(&self) -> U32Digits<'_> {
+        self.data.iter_u32_digits()
+    }
 
     /// Returns an iterator of `u64` digits representation of the [`BigInt`] ordered least
     /// significant digit first.
@@ -810,7 +834,7 @@ impl BigInt {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigInt;
+    /// use rust_monty_parallel::BigInt;
     ///
     /// assert_eq!(BigInt::from(-1125).iter_u64_digits().collect::<Vec<u64>>(), vec![1125u64]);
     /// assert_eq!(BigInt::from(4294967295u32).iter_u64_digits().collect::<Vec<u64>>(), vec![4294967295u64]);
@@ -819,10 +843,10 @@ impl BigInt {
     /// assert_eq!(BigInt::from(112500000000i64).iter_u64_digits().collect::<Vec<u64>>(), vec![112500000000u64]);
     /// assert_eq!(BigInt::from(1u128 << 64).iter_u64_digits().collect::<Vec<u64>>(), vec![0, 1]);
     /// ```
-    // #[inline]
-    // pub fn iter_u64_digits(&self) -> U64Digits<'_> {
-    //     self.data.iter_u64_digits()
-    // }
+    #[inline]
+    pub fn iter_u64_digits(&self) -> U64Digits<'_> {
+        self.data.iter_u64_digits()
+    }
 
     /// Returns the two's-complement byte representation of the [`BigInt`] in big-endian byte order.
     ///
@@ -972,6 +996,78 @@ impl BigInt {
         self.data.bits()
     }
 
+    /// Returns the base 2 logarithm of `self`, rounded down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not positive.
+    #[inline]
+    pub fn ilog2(&self) -> u64 {
+        self.checked_ilog2()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down, or `None` if `self` is not
+    /// positive.
+    #[inline]
+    pub fn checked_ilog2(&self) -> Option<u64> {
+        match self.sign {
+            Plus => self.data.checked_ilog2(),
+            NoSign | Minus => None,
+        }
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down --
+    /// see [`BigUint::ilog10()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not positive.
+    #[inline]
+    pub fn ilog10(&self) -> u64 {
+        self.checked_ilog10()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down, or `None` if `self` is not
+    /// positive.
+    #[inline]
+    pub fn checked_ilog10(&self) -> Option<u64> {
+        match self.sign {
+            Plus => self.data.checked_ilog10(),
+            NoSign | Minus => None,
+        }
+    }
+
+    /// Returns the logarithm of `self` in `base`, rounded down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not positive, or if `base` is less than 2.
+    pub fn ilog(&self, base: &BigInt) -> u64 {
+        assert!(
+            base.sign == Plus && base.data.bits() > 1,
+            "base of integer logarithm must be at least 2"
+        );
+        self.checked_ilog(base)
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the logarithm of `self` in `base`, rounded down, or `None` if `self` is not
+    /// positive or `base` is less than 2.
+    pub fn checked_ilog(&self, base: &BigInt) -> Option<u64> {
+        match (self.sign, base.sign) {
+            (Plus, Plus) => self.data.checked_ilog(&base.data),
+            _ => None,
+        }
+    }
+
+    /// Returns `10^n` -- see [`BigUint::pow10()`].
+    #[inline]
+    pub fn pow10(n: u64) -> BigInt {
+        BigInt::from(BigUint::pow10(n))
+    }
+
     /// Converts this [`BigInt`] into a [`BigUint`], if it's not negative.
     #[inline]
     pub fn to_biguint(&self) -> Option<BigUint> {
@@ -1005,6 +1101,71 @@ impl BigInt {
         Some(self / v)
     }
 
+    /// Returns `self / d`, where `d` is known to divide `self` exactly.
+    ///
+    /// See [`BigUint::div_exact`].
+    ///
+    /// Panics if `d` is zero.
+    pub fn div_exact(&self, d: &Self) -> Self {
+        BigInt::from_biguint(self.sign * d.sign, self.data.div_exact(&d.data))
+    }
+
+    /// Returns `self * self`, which is faster than the general product.
+    pub fn square(&self) -> Self {
+        BigInt::from(self.data.square())
+    }
+
+    /// Adds `sign * p` to self, where `add` adds the magnitude p to a `BigUint`, and `sub`
+    /// replaces one with its absolute difference from p, returning whether p was larger.
+    fn add_product<F, G>(&mut self, sign: Sign, add: F, sub: G)
+    where
+        F: FnOnce(&mut BigUint),
+        G: FnOnce(&mut BigUint) -> bool,
+    {
+        if sign == NoSign {
+            return;
+        }
+        if self.sign == NoSign || self.sign == sign {
+            self.sign = sign;
+            add(&mut self.data);
+        } else if sub(&mut self.data) {
+            self.sign = sign;
+        }
+        if self.data.is_zero() {
+            self.sign = NoSign;
+        }
+    }
+
+    /// Sets `self += a * b`, accumulating the product directly into `self` rather than
+    /// allocating it separately where possible.
+    pub fn add_mul_assign(&mut self, a: &BigInt, b: &BigInt) {
+        self.add_product(
+            a.sign * b.sign,
+            |acc| add_mul(acc, &a.data, &b.data),
+            |acc| sub_mul(acc, &a.data, &b.data),
+        );
+    }
+
+    /// Sets `self -= a * b`, accumulating the product directly into `self` rather than
+    /// allocating it separately where possible.
+    pub fn sub_mul_assign(&mut self, a: &BigInt, b: &BigInt) {
+        self.add_product(
+            -(a.sign * b.sign),
+            |acc| add_mul(acc, &a.data, &b.data),
+            |acc| sub_mul(acc, &a.data, &b.data),
+        );
+    }
+
+    /// Sets `self += a * b` for a single digit `b`.
+    pub fn add_mul_digit_assign(&mut self, a: &BigInt, b: u32) {
+        let sign = if b == 0 { NoSign } else { a.sign };
+        self.add_product(
+            sign,
+            |acc| add_mul_digit(acc, &a.data, b as BigDigit),
+            |acc| sub_mul_digit(acc, &a.data, b as BigDigit),
+        );
+    }
+
     /// Returns `self ^ exponent`.
     pub fn pow(&self, exponent: u32) -> Self {
         Pow::pow(self, exponent)
@@ -1128,22 +1289,49 @@ impl BigInt {
         }
     }
 
-    // pub fn set_bit(&mut self, bit: u64, value: bool) {
-    //     match self.sign {
-    //         Sign::Plus => self.data.set_bit(bit, value),
-    //         Sign::Minus => bits::set_negative_bit(self, bit, value),
-    //         Sign::NoSign => {
-    //             if value {
-    //                 self.data.set_bit(bit, true);
-    //                 self.sign = Sign::Plus;
-    //             } else {
-    //                 // Clearing a bit for zero is a no-op
-    //             }
-    //         }
-    //     }
-    //     // The top bit may have been cleared, so normalize
-    //     self.normalize();
-    // }
+    /// Sets or clears the bit in position `bit`, using the two's complement for negative numbers
+    ///
+    /// Note that setting or clearing a bit beyond the current bit length may need a reallocation
+    /// to store the new digits
+    pub fn set_bit(&mut self, bit: u64, value: bool) {
+        match self.sign {
+            Plus => self.data.set_bit(bit, value),
+            Minus => bits::set_negative_bit(self, bit, value),
+            NoSign => {
+                if value {
+                    self.data.set_bit(bit, true);
+                    self.sign = Plus;
+                } else {
+                    // Clearing a bit for zero is a no-op
+                }
+            }
+        }
+        // The top bit may have been cleared, so normalize
+        self.normalize();
+    }
+
+    /// Returns the number of one bits, or `None` for negative numbers, whose two's complement
+    /// has infinitely many.
+    pub fn count_ones(&self) -> Option<u64> {
+        match self.sign {
+            Minus => None,
+            NoSign | Plus => Some(self.data.count_ones()),
+        }
+    }
+
+    /// Returns the number of least-significant bits that are one, using the two's complement
+    /// for negative numbers, or `None` for -1, whose bits are all ones.
+    pub fn trailing_ones(&self) -> Option<u64> {
+        match self.sign {
+            Minus => bits::negative_trailing_ones(self),
+            NoSign | Plus => Some(self.data.trailing_ones()),
+        }
+    }
+
+    /// Returns `true` if `self` is a power of two, which no negative number or zero is.
+    pub fn is_power_of_two(&self) -> bool {
+        self.sign == Plus && self.data.is_power_of_two()
+    }
 }
 
 impl num_traits::FromBytes for BigInt {
@@ -1218,3 +1406,51 @@ fn test_assign_from_slice() {
     check(Minus, 1, Minus, 1);
     check(NoSign, 1, NoSign, 0);
 }
+
+#[test]
+fn test_div_exact() {
+    let a = BigInt::from(-6_000_000_000_000i64);
+    assert_eq!(
+        a.div_exact(&BigInt::from(3)),
+        BigInt::from(-2_000_000_000_000i64)
+    );
+    assert_eq!(
+        a.div_exact(&BigInt::from(-3)),
+        BigInt::from(2_000_000_000_000i64)
+    );
+    assert_eq!(BigInt::ZERO.div_exact(&BigInt::from(-3)), BigInt::ZERO);
+}
+
+#[test]
+fn test_mul_add_assign() {
+    let values = [-1_000_000_000_007i64, -3, 0, 5, 999_999_999_989];
+    for &c in &values {
+        for &a in &values {
+            for &b in &values {
+                let expected = i128::from(c) + i128::from(a) * i128::from(b);
+                let (a, b) = (BigInt::from(a), BigInt::from(b));
+                let mut acc = BigInt::from(c);
+                acc.add_mul_assign(&a, &b);
+                assert_eq!(acc, BigInt::from(expected));
+                acc.sub_mul_assign(&a, &b);
+                assert_eq!(acc, BigInt::from(c));
+
+                let mut acc = BigInt::from(c);
+                acc.add_mul_digit_assign(&a, 7);
+                assert_eq!(acc, BigInt::from(c) + &a * 7u32);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ilog() {
+    let x = BigInt::pow10(30) * 7u32;
+    assert_eq!(x.ilog10(), 30);
+    assert_eq!(x.checked_ilog2(), Some(x.bits() - 1));
+    assert_eq!(x.ilog(&BigInt::from(1000)), 10);
+    assert_eq!((-&x).checked_ilog10(), None);
+    assert_eq!(BigInt::ZERO.checked_ilog2(), None);
+    assert_eq!(x.checked_ilog(&BigInt::from(-10)), None);
+    assert_eq!(x.checked_ilog(&BigInt::from(1)), None);
+}
@@ -6,7 +6,7 @@ use crate::{IsizePromotion, UsizePromotion};
 
 use core::iter::Product;
 use core::ops::{Mul, MulAssign};
-use num_traits::{CheckedMul, One, Zero};
+use num_traits::{CheckedMul, MulAdd, MulAddAssign, One, Zero};
 
 impl Mul<Sign> for Sign {
     type Output = Sign;
@@ -215,3 +215,41 @@ impl CheckedMul for BigInt {
 }
 
 impl_product_iter_type!(BigInt);
+
+impl MulAdd<BigInt, BigInt> for BigInt {
+    type Output = BigInt;
+
+    #[inline]
+    fn mul_add(self, a: BigInt, mut b: BigInt) -> BigInt {
+        b.add_mul_assign(&self, &a);
+        b
+    }
+}
+
+impl MulAdd<&BigInt, &BigInt> for &BigInt {
+    type Output = BigInt;
+
+    #[inline]
+    fn mul_add(self, a: &BigInt, b: &BigInt) -> BigInt {
+        let mut acc = b.clone();
+        acc.add_mul_assign(self, a);
+        acc
+    }
+}
+
+impl MulAddAssign<BigInt, BigInt> for BigInt {
+    #[inline]
+    fn mul_add_assign(&mut self, a: BigInt, mut b: BigInt) {
+        b.add_mul_assign(self, &a);
+        *self = b;
+    }
+}
+
+impl MulAddAssign<&BigInt, &BigInt> for BigInt {
+    #[inline]
+    fn mul_add_assign(&mut self, a: &BigInt, b: &BigInt) {
+        let mut acc = b.clone();
+        acc.add_mul_assign(self, a);
+        *self = acc;
+    }
+}
@@ -8,23 +8,55 @@ use core::default::Default;
 use core::fmt;
 use core::hash;
 use core::mem;
+use core::ops::Range;
 use core::str;
 
 use num_integer::{Integer, Roots};
 use num_traits::{ConstZero, Num, One, Pow, ToPrimitive, Unsigned, Zero};
 
 mod addition;
+mod bits;
 mod division;
 mod multiplication;
 mod subtraction;
 
+mod combinatorics;
 mod convert;
+mod divisor;
+mod iter;
+mod log;
 mod monty;
+mod ntt;
 mod power;
+mod prime;
+mod randprime;
+mod roots;
 mod shift;
+mod sieve;
 
 pub(crate) use self::convert::to_str_radix_reversed;
-// pub use self::iter::{U32Digits, U64Digits};
+pub(crate) use self::division::{div_rem_digit, div_rem_normalized};
+pub use self::divisor::DigitDivisor;
+pub use self::iter::{IterBits, IterOnes, U32Digits, U64Digits};
+pub(crate) use self::monty::montgomery_digits;
+pub(crate) use self::multiplication::{
+    add_mul, add_mul_digit, mul_high_digits, mul_low_digits, mul_slices, sqr_slices, sub_mul,
+    sub_mul_digit,
+};
+pub use self::prime::PrimeIter;
+#[cfg(feature = "rand")]
+pub use self::randprime::RandPrime;
+pub(crate) use self::shift::shl_bits;
+
+// The digit kernels that `limbs` wraps when the limbs are the digits.
+cfg_64!(
+    pub(crate) use self::{
+        addition::__add2,
+        division::sub_mul_digit_same_len,
+        monty::{add_mul_vvw_serial, sub_vv},
+        shift::shr_bits,
+    };
+);
 
 /// A big unsigned integer type.
 pub struct BigUint {
@@ -254,7 +286,7 @@ impl Integer for BigUint {
         if self.is_zero() && other.is_zero() {
             Self::ZERO
         } else {
-            self / self.gcd(other) * other
+            self.div_exact(&self.gcd(other)) * other
         }
     }
 
@@ -266,7 +298,7 @@ impl Integer for BigUint {
         let lcm = if gcd.is_zero() {
             Self::ZERO
         } else {
-            self / &gcd * other
+            self.div_exact(&gcd) * other
         };
         (gcd, lcm)
     }
@@ -327,185 +359,30 @@ impl Integer for BigUint {
     }
 }
 
-#[inline]
-fn fixpoint<F>(mut x: BigUint, max_bits: u64, f: F) -> BigUint
-where
-    F: Fn(&BigUint) -> BigUint,
-{
-    let mut xn = f(&x);
-
-    // If the value increased, then the initial guess must have been low.
-    // Repeat until we reverse course.
-    while x < xn {
-        // Sometimes an increase will go way too far, especially with large
-        // powers, and then take a long time to walk back.  We know an upper
-        // bound based on bit size, so saturate on that.
-        x = if xn.bits() > max_bits {
-            BigUint::one() << max_bits
-        } else {
-            xn
-        };
-        xn = f(&x);
-    }
-
-    // Now keep repeating while the estimate is decreasing.
-    while x > xn {
-        x = xn;
-        xn = f(&x);
-    }
-    x
-}
-
 impl Roots for BigUint {
-    // nth_root, sqrt and cbrt use Newton's method to compute
-    // principal root of a given degree for a given integer.
-
-    // Reference:
-    // Brent & Zimmermann, Modern Computer Arithmetic, v0.5.9, Algorithm 1.14
+    // nth_root and cbrt use Newton's method with precision doubling, and sqrt uses
+    // Karatsuba square root, see the `roots` module.
     fn nth_root(&self, n: u32) -> Self {
         assert!(n > 0, "root degree n must be at least 1");
 
-        if self.is_zero() || self.is_one() {
-            return self.clone();
-        }
-
-        match n {
-            // Optimize for small n
-            1 => return self.clone(),
-            2 => return self.sqrt(),
-            3 => return self.cbrt(),
-            _ => (),
-        }
-
-        // The root of non-zero values less than 2ⁿ can only be 1.
-        let bits = self.bits();
-        let n64 = u64::from(n);
-        if bits <= n64 {
-            return BigUint::one();
-        }
-
-        // If we fit in `u64`, compute the root that way.
-        if let Some(x) = self.to_u64() {
-            return x.nth_root(n).into();
+        if n == 1 {
+            self.clone()
+        } else {
+            roots::nth_root(self, n)
         }
-
-        let max_bits = bits / n64 + 1;
-
-        #[cfg(feature = "std")]
-        let guess = match self.to_f64() {
-            Some(f) if f.is_finite() => {
-                use num_traits::FromPrimitive;
-
-                // We fit in `f64` (lossy), so get a better initial guess from that.
-                BigUint::from_f64((f.ln() / f64::from(n)).exp()).unwrap()
-            }
-            _ => {
-                // Try to guess by scaling down such that it does fit in `f64`.
-                // With some (x * 2ⁿᵏ), its nth root ≈ (ⁿ√x * 2ᵏ)
-                let extra_bits = bits - (f64::MAX_EXP as u64 - 1);
-                let root_scale = Integer::div_ceil(&extra_bits, &n64);
-                let scale = root_scale * n64;
-                if scale < bits && bits - scale > n64 {
-                    (self >> scale).nth_root(n) << root_scale
-                } else {
-                    BigUint::one() << max_bits
-                }
-            }
-        };
-
-        #[cfg(not(feature = "std"))]
-        let guess = BigUint::one() << max_bits;
-
-        let n_min_1 = n - 1;
-        fixpoint(guess, max_bits, move |s| {
-            let q = self / s.pow(n_min_1);
-            let t = n_min_1 * s + q;
-            t / n
-        })
     }
 
-    // Reference:
-    // Brent & Zimmermann, Modern Computer Arithmetic, v0.5.9, Algorithm 1.13
     fn sqrt(&self) -> Self {
-        if self.is_zero() || self.is_one() {
-            return self.clone();
-        }
-
         // If we fit in `u64`, compute the root that way.
         if let Some(x) = self.to_u64() {
             return x.sqrt().into();
         }
 
-        let bits = self.bits();
-        let max_bits = bits / 2 + 1;
-
-        #[cfg(feature = "std")]
-        let guess = match self.to_f64() {
-            Some(f) if f.is_finite() => {
-                use num_traits::FromPrimitive;
-
-                // We fit in `f64` (lossy), so get a better initial guess from that.
-                BigUint::from_f64(f.sqrt()).unwrap()
-            }
-            _ => {
-                // Try to guess by scaling down such that it does fit in `f64`.
-                // With some (x * 2²ᵏ), its sqrt ≈ (√x * 2ᵏ)
-                let extra_bits = bits - (f64::MAX_EXP as u64 - 1);
-                let root_scale = (extra_bits + 1) / 2;
-                let scale = root_scale * 2;
-                (self >> scale).sqrt() << root_scale
-            }
-        };
-
-        #[cfg(not(feature = "std"))]
-        let guess = BigUint::one() << max_bits;
-
-        fixpoint(guess, max_bits, move |s| {
-            let q = self / s;
-            let t = s + q;
-            t >> 1
-        })
+        roots::sqrt_rem(self).0
     }
 
     fn cbrt(&self) -> Self {
-        if self.is_zero() || self.is_one() {
-            return self.clone();
-        }
-
-        // If we fit in `u64`, compute the root that way.
-        if let Some(x) = self.to_u64() {
-            return x.cbrt().into();
-        }
-
-        let bits = self.bits();
-        let max_bits = bits / 3 + 1;
-
-        #[cfg(feature = "std")]
-        let guess = match self.to_f64() {
-            Some(f) if f.is_finite() => {
-                use num_traits::FromPrimitive;
-
-                // We fit in `f64` (lossy), so get a better initial guess from that.
-                BigUint::from_f64(f.cbrt()).unwrap()
-            }
-            _ => {
-                // Try to guess by scaling down such that it does fit in `f64`.
-                // With some (x * 2³ᵏ), its cbrt ≈ (∛x * 2ᵏ)
-                let extra_bits = bits - (f64::MAX_EXP as u64 - 1);
-                let root_scale = (extra_bits + 2) / 3;
-                let scale = root_scale * 3;
-                (self >> scale).cbrt() << root_scale
-            }
-        };
-
-        #[cfg(not(feature = "std"))]
-        let guess = BigUint::one() << max_bits;
-
-        fixpoint(guess, max_bits, move |s| {
-            let q = self / (s * s);
-            let t = (s << 1) + q;
-            t / 3u32
-        })
+        roots::nth_root(self, 3)
     }
 }
 
@@ -570,6 +447,50 @@ impl BigUint {
         self.normalize();
     }
 
+    /// Creates and initializes a [`BigUint`] from `u64` digits.
+    ///
+    /// The base 2<sup>64</sup> digits are ordered least significant digit first. On 64-bit
+    /// targets the vector becomes the digits of the result without copying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from_u64_digits(vec![0, 1]);
+    /// assert_eq!(x, BigUint::from(1u128 << 64));
+    /// assert_eq!(x.to_u64_digits(), vec![0, 1]);
+    /// ```
+    #[inline]
+    pub fn from_u64_digits(digits: Vec<u64>) -> BigUint {
+        let mut big = Self::ZERO;
+
+        cfg_digit_expr!(big.assign_from_u64_slice(&digits), {
+            big.data = digits;
+            big.normalize();
+        });
+
+        big
+    }
+
+    /// Assign a value to a [`BigUint`] from `u64` digits.
+    ///
+    /// The base 2<sup>64</sup> digits are ordered least significant digit first.
+    #[inline]
+    pub fn assign_from_u64_slice(&mut self, slice: &[u64]) {
+        self.data.clear();
+
+        cfg_digit_expr!(
+            self.data.extend(slice.iter().flat_map(|&digit| {
+                let (hi, lo) = big_digit::from_doublebigdigit(digit);
+                [lo, hi]
+            })),
+            self.data.extend_from_slice(slice)
+        );
+
+        self.normalize();
+    }
+
     /// Creates and initializes a [`BigUint`].
     ///
     /// The bytes are in big-endian byte order.
@@ -715,17 +636,17 @@ impl BigUint {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigUint;
+    /// use rust_monty_parallel::BigUint;
     ///
     /// assert_eq!(BigUint::from(1125u32).to_u32_digits(), vec![1125]);
     /// assert_eq!(BigUint::from(4294967295u32).to_u32_digits(), vec![4294967295]);
     /// assert_eq!(BigUint::from(4294967296u64).to_u32_digits(), vec![0, 1]);
     /// assert_eq!(BigUint::from(112500000000u64).to_u32_digits(), vec![830850304, 26]);
     /// ```
-    // #[inline]
-    // pub fn to_u32_digits(&self) -> Vec<u32> {
-    //     self.iter_u32_digits().collect()
-    // }
+    #[inline]
+    pub fn to_u32_digits(&self) -> Vec<u32> {
+        self.iter_u32_digits().collect()
+    }
 
     /// Returns the `u64` digits representation of the [`BigUint`] ordered least significant digit
     /// first.
@@ -733,7 +654,7 @@ impl BigUint {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigUint;
+    /// use rust_monty_parallel::BigUint;
     ///
     /// assert_eq!(BigUint::from(1125u32).to_u64_digits(), vec![1125]);
     /// assert_eq!(BigUint::from(4294967295u32).to_u64_digits(), vec![4294967295]);
@@ -741,10 +662,10 @@ impl BigUint {
     /// assert_eq!(BigUint::from(112500000000u64).to_u64_digits(), vec![112500000000]);
     /// assert_eq!(BigUint::from(1u128 << 64).to_u64_digits(), vec![0, 1]);
     /// ```
-    // #[inline]
-    // pub fn to_u64_digits(&self) -> Vec<u64> {
-    //     self.iter_u64_digits().collect()
-    // }
+    #[inline]
+    pub fn to_u64_digits(&self) -> Vec<u64> {
+        self.iter_u64_digits().collect()
+    }
 
     /// Returns an iterator of `u32` digits representation of the [`BigUint`] ordered least
     /// significant digit first.
@@ -752,17 +673,17 @@ impl BigUint {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigUint;
+    /// use rust_monty_parallel::BigUint;
     ///
     /// assert_eq!(BigUint::from(1125u32).iter_u32_digits().collect::<Vec<u32>>(), vec![1125]);
     /// assert_eq!(BigUint::from(4294967295u32).iter_u32_digits().collect::<Vec<u32>>(), vec![4294967295]);
     /// assert_eq!(BigUint::from(4294967296u64).iter_u32_digits().collect::<Vec<u32>>(), vec![0, 1]);
     /// assert_eq!(BigUint::from(112500000000u64).iter_u32_digits().collect::<Vec<u32>>(), vec![830850304, 26]);
     /// ```
-    // #[inline]
-    // pub fn iter_u32_digits(&self) -> U32Digits<'_> {
-    //     U32Digits::new(self.data.as_slice())
-    // }
+    #[inline]
+    pub fn iter_u32_digits(&self) -> U32Digits<'_> {
+        U32Digits::new(self.data.as_slice())
+    }
 
     /// Returns an iterator of `u64` digits representation of the [`BigUint`] ordered least
     /// significant digit first.
@@ -770,7 +691,7 @@ impl BigUint {
     /// # Examples
     ///
     /// ```
-    /// use num_bigint::BigUint;
+    /// use rust_monty_parallel::BigUint;
     ///
     /// assert_eq!(BigUint::from(1125u32).iter_u64_digits().collect::<Vec<u64>>(), vec![1125]);
     /// assert_eq!(BigUint::from(4294967295u32).iter_u64_digits().collect::<Vec<u64>>(), vec![4294967295]);
@@ -778,10 +699,10 @@ impl BigUint {
     /// assert_eq!(BigUint::from(112500000000u64).iter_u64_digits().collect::<Vec<u64>>(), vec![112500000000]);
     /// assert_eq!(BigUint::from(1u128 << 64).iter_u64_digits().collect::<Vec<u64>>(), vec![0, 1]);
     /// ```
-    // #[inline]
-    // pub fn iter_u64_digits(&self) -> U64Digits<'_> {
-    //     U64Digits::new(self.data.as_slice())
-    // }
+    #[inline]
+    pub fn iter_u64_digits(&self) -> U64Digits<'_> {
+        U64Digits::new(self.data.as_slice())
+    }
 
     /// Returns the integer formatted as a string in the given radix.
     /// `radix` must be in the range `2...36`.
@@ -851,6 +772,194 @@ impl BigUint {
         self.data.len() as u64 * u64::from(big_digit::BITS) - zeros
     }
 
+    /// Returns the base 2 logarithm of `self`, rounded down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is zero.
+    #[inline]
+    pub fn ilog2(&self) -> u64 {
+        self.checked_ilog2()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 2 logarithm of `self`, rounded down, or `None` if `self` is zero.
+    #[inline]
+    pub fn checked_ilog2(&self) -> Option<u64> {
+        self.bits().checked_sub(1)
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down.
+    ///
+    /// This is exact: an estimate from the bit length is corrected by comparing against powers of
+    /// ten, with no floating point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::pow10(40);
+    /// assert_eq!(x.ilog10(), 40);
+    /// assert_eq!((x - 1u32).ilog10(), 39);
+    /// ```
+    #[inline]
+    pub fn ilog10(&self) -> u64 {
+        self.checked_ilog10()
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the base 10 logarithm of `self`, rounded down, or `None` if `self` is zero.
+    #[inline]
+    pub fn checked_ilog10(&self) -> Option<u64> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(log::ilog10(self))
+        }
+    }
+
+    /// Returns the logarithm of `self` in `base`, rounded down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is zero, or if `base` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let base = BigUint::from(7u32);
+    /// assert_eq!(base.pow(30).ilog(&base), 30);
+    /// assert_eq!((base.pow(30) - 1u32).ilog(&base), 29);
+    /// ```
+    pub fn ilog(&self, base: &BigUint) -> u64 {
+        assert!(
+            base.bits() > 1,
+            "base of integer logarithm must be at least 2"
+        );
+        self.checked_ilog(base)
+            .expect("argument of integer logarithm must be positive")
+    }
+
+    /// Returns the logarithm of `self` in `base`, rounded down, or `None` if `self` is zero or
+    /// `base` is less than 2.
+    pub fn checked_ilog(&self, base: &BigUint) -> Option<u64> {
+        if self.is_zero() || base.bits() <= 1 {
+            None
+        } else {
+            Some(log::ilog(self, base))
+        }
+    }
+
+    /// Returns whether `self` is probably prime, after trial division by small primes and
+    /// `rounds` rounds of the Miller–Rabin test, with at least one.
+    ///
+    /// The first round is to base 2, and the others to bases derived deterministically from
+    /// `self`. A random composite passes each round with probability at most 1/4, and every
+    /// number below 2^64 gets an exact answer. Since anyone can compute the bases, a composite
+    /// can be constructed to pass, so this is not meant for adversarial input. The rounds share
+    /// one Montgomery context, and for large numbers they run in parallel on the current
+    /// [`executor`](crate::executor).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let m127 = (BigUint::from(1u32) << 127u8) - 1u32;
+    /// assert!(m127.is_probably_prime(20));
+    /// assert!(!(m127 + 2u32).is_probably_prime(20));
+    /// ```
+    pub fn is_probably_prime(&self, rounds: usize) -> bool {
+        prime::is_probably_prime(self, rounds)
+    }
+
+    /// Returns whether `self` passes the Baillie–PSW test: trial division by small primes, a
+    /// strong probable-prime test to base 2 and a strong Lucas test.
+    ///
+    /// No composite is known to pass this test, and none below 2^64 does.
+    pub fn is_prime_bpsw(&self) -> bool {
+        prime::is_prime_bpsw(self)
+    }
+
+    /// Returns the smallest prime greater than or equal to `self`.
+    ///
+    /// This sieves segments from `self` up and tests what survives with
+    /// [`is_prime_bpsw`](Self::is_prime_bpsw), as [`PrimeIter`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from(1u32) << 64u8;
+    /// assert_eq!(x.next_prime(), &x + 13u32);
+    /// assert_eq!(BigUint::from(13u32).next_prime(), BigUint::from(13u32));
+    /// ```
+    pub fn next_prime(&self) -> Self {
+        prime::next_prime(self)
+    }
+
+    /// Returns the largest prime less than or equal to `self`, or `None` if `self` is below 2.
+    ///
+    /// This sieves segments from `self` down and tests what survives with
+    /// [`is_prime_bpsw`](Self::is_prime_bpsw), from the top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from(1u32) << 64u8;
+    /// assert_eq!(x.prev_prime(), Some(&x - 59u32));
+    /// assert_eq!(BigUint::from(1u32).prev_prime(), None);
+    /// ```
+    pub fn prev_prime(&self) -> Option<Self> {
+        prime::prev_prime(self)
+    }
+
+    /// Returns `true` if `self` is a power of two.
+    pub fn is_power_of_two(&self) -> bool {
+        match self.data.split_last() {
+            Some((last, rest)) => last.is_power_of_two() && rest.iter().all(Zero::is_zero),
+            None => false,
+        }
+    }
+
+    /// Returns the smallest power of two greater than or equal to `self`.
+    pub fn next_power_of_two(&self) -> BigUint {
+        if self.is_power_of_two() {
+            self.clone()
+        } else {
+            // zero has no bits, so this gives one
+            BigUint::one() << self.bits()
+        }
+    }
+
+    /// Returns `10^n`.
+    ///
+    /// This multiplies squares `5^(2^k)` and shifts the product. The squares up to a fixed size
+    /// are cached and shared across calls and threads, and larger ones are computed on each call,
+    /// as is the product itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// assert_eq!(BigUint::pow10(3), BigUint::from(1000u32));
+    /// assert_eq!(BigUint::pow10(50).to_string().len(), 51);
+    /// ```
+    pub fn pow10(n: u64) -> BigUint {
+        log::pow5(n) << n
+    }
+
     /// Strips off trailing zero bigdigits - comparisons require the last element in the vector to
     /// be nonzero.
     #[inline]
@@ -871,6 +980,97 @@ impl BigUint {
         self
     }
 
+    /// Returns `self / d`, where `d` is known to divide `self` exactly.
+    ///
+    /// This is faster than the general division, because the quotient can be found from the least
+    /// significant digits up. If `d` does not divide `self` the result is meaningless, and debug
+    /// builds panic.
+    ///
+    /// Panics if `d` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let a = BigUint::from(3u32).pow(100);
+    /// let d = BigUint::from(3u32).pow(40);
+    /// assert_eq!(a.div_exact(&d), BigUint::from(3u32).pow(60));
+    /// ```
+    pub fn div_exact(&self, d: &Self) -> Self {
+        let q = division::div_exact(self, d);
+        debug_assert!(
+            &q * d == *self,
+            "div_exact: the divisor does not divide the dividend"
+        );
+        q
+    }
+
+    /// Returns `self * self`, which is faster than the general product.
+    pub fn square(&self) -> Self {
+        multiplication::square(self)
+    }
+
+    /// Returns the low `n` 64-bit limbs of `self * other`, i.e. `self * other mod 2^(64 n)`.
+    ///
+    /// Digit products that only affect higher limbs are never computed, which makes this faster
+    /// than reducing the full product, e.g. for Newton's iteration modulo a power of two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let a = BigUint::from(u64::MAX) << 64u32;
+    /// let b = BigUint::from(3u32) + (BigUint::from(5u32) << 64u32);
+    /// assert_eq!(a.mul_low(&b, 2), (&a * &b) % (BigUint::from(1u32) << 128u32));
+    /// ```
+    pub fn mul_low(&self, other: &Self, n: usize) -> Self {
+        let digits = n * (64 / big_digit::BITS as usize);
+        biguint_from_vec(mul_low_digits(&self.data, &other.data, digits))
+    }
+
+    /// Returns `self * other` with the low `n` 64-bit limbs discarded, i.e.
+    /// `(self * other) >> (64 * n)`.
+    ///
+    /// Most digit products that only affect the discarded limbs are never computed, which makes
+    /// this faster than shifting the full product, e.g. for Barrett reduction. The result is
+    /// always exact.
+    pub fn mul_high(&self, other: &Self, n: usize) -> Self {
+        let digits = n * (64 / big_digit::BITS as usize);
+        mul_high_digits(&self.data, &other.data, digits)
+    }
+
+    /// Sets `self += a * b`, accumulating the product directly into `self` rather than
+    /// allocating it separately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let mut acc = BigUint::from(7u32);
+    /// acc.add_mul_assign(&BigUint::from(6u32), &BigUint::from(9u32));
+    /// assert_eq!(acc, BigUint::from(61u32));
+    /// ```
+    pub fn add_mul_assign(&mut self, a: &Self, b: &Self) {
+        add_mul(self, a, b);
+    }
+
+    /// Sets `self -= a * b`, without allocating the product separately where possible.
+    ///
+    /// Panics if `a * b` is larger than `self`.
+    pub fn sub_mul_assign(&mut self, a: &Self, b: &Self) {
+        if sub_mul(self, a, b) {
+            panic!("Cannot subtract a * b from self because a * b is larger than self.");
+        }
+    }
+
+    /// Sets `self += a * b` for a single digit `b`.
+    pub fn add_mul_digit_assign(&mut self, a: &Self, b: u32) {
+        add_mul_digit(self, a, b as BigDigit);
+    }
+
     /// Returns `self ^ exponent`.
     pub fn pow(&self, exponent: u32) -> Self {
         Pow::pow(self, exponent)
@@ -969,6 +1169,33 @@ impl BigUint {
         Roots::sqrt(self)
     }
 
+    /// Returns the truncated principal square root of `self` and the remainder, `(s, r)` with
+    /// `s * s + r == self`.
+    ///
+    /// This uses Zimmermann's Karatsuba square root, which costs a small constant number of
+    /// multiplications of the same size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let (s, r) = BigUint::from(1000u32).sqrt_rem();
+    /// assert_eq!(s, BigUint::from(31u32));
+    /// assert_eq!(r, BigUint::from(39u32));
+    /// ```
+    pub fn sqrt_rem(&self) -> (Self, Self) {
+        roots::sqrt_rem(self)
+    }
+
+    /// Returns `true` if `self` is a perfect square, including zero and one.
+    ///
+    /// Most non-squares are ruled out by quadratic residue tests modulo small numbers, without
+    /// computing the square root.
+    pub fn is_square(&self) -> bool {
+        roots::is_square(self)
+    }
+
     /// Returns the truncated principal cube root of `self` --
     /// see [Roots::cbrt](https://docs.rs/num-integer/0.1/num_integer/trait.Roots.html#method.cbrt).
     pub fn cbrt(&self) -> Self {
@@ -981,6 +1208,79 @@ impl BigUint {
         Roots::nth_root(self, n)
     }
 
+    /// Returns the truncated principal `n`th root of `self` and the remainder, `(r, rem)` with
+    /// `r.pow(n) + rem == self`.
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let (r, rem) = BigUint::from(1000u32).nth_root_rem(5);
+    /// assert_eq!(r, BigUint::from(3u32));
+    /// assert_eq!(rem, BigUint::from(757u32));
+    /// ```
+    pub fn nth_root_rem(&self, n: u32) -> (Self, Self) {
+        let root = Roots::nth_root(self, n);
+        let rem = self - Pow::pow(&root, n);
+        (root, rem)
+    }
+
+    /// Returns `(a, k)` with `self == a.pow(k)` for the largest such `k >= 2`, or `None` if
+    /// `self` is not a perfect power.
+    ///
+    /// Zero and one are perfect powers of any degree, for which this returns `(self, 2)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let n = BigUint::from(6u32).pow(10);
+    /// assert_eq!(n.is_perfect_power(), Some((BigUint::from(6u32), 10)));
+    /// assert_eq!((n + 1u32).is_perfect_power(), None);
+    /// ```
+    pub fn is_perfect_power(&self) -> Option<(Self, u32)> {
+        roots::perfect_power(self)
+    }
+
+    /// Returns `n!`, the product of all integers from 1 to `n`.
+    pub fn factorial(n: u64) -> Self {
+        combinatorics::factorial(n)
+    }
+
+    /// Returns `n!!`, the product of all integers from 1 to `n` with the same parity as `n`.
+    pub fn double_factorial(n: u64) -> Self {
+        combinatorics::double_factorial(n)
+    }
+
+    /// Returns the binomial coefficient `n` choose `k`, which is zero if `k > n`.
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// assert_eq!(BigUint::binomial(52, 5), BigUint::from(2_598_960u32));
+    /// assert_eq!(BigUint::binomial(5, 6), BigUint::from(0u32));
+    /// ```
+    pub fn binomial(n: u64, k: u64) -> Self {
+        combinatorics::binomial(n, k)
+    }
+
+    /// Returns the multinomial coefficient `(k₁ + k₂ + ...)! / (k₁! k₂! ...)`, the number of
+    /// ways to split a set into parts of the given sizes.
+    ///
+    /// Panics if the sum of `ks` overflows a `u64`.
+    pub fn multinomial(ks: &[u64]) -> Self {
+        combinatorics::multinomial(ks)
+    }
+
+    /// Returns `n#`, the product of all primes less than or equal to `n`.
+    pub fn primorial(n: u64) -> Self {
+        combinatorics::primorial(n)
+    }
+
     /// Returns the number of least-significant bits that are zero,
     /// or `None` if the entire number is zero.
     pub fn trailing_zeros(&self) -> Option<u64> {
@@ -1038,6 +1338,222 @@ impl BigUint {
             self.normalize();
         }
     }
+
+    /// Returns the bits of `self` in `range`, shifted down so that `range.start` is bit 0.
+    ///
+    /// Bits past the bit length are zero, so the range may extend beyond it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range.start > range.end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from(0b1011_0110u32);
+    /// assert_eq!(x.bits_range(2..6), BigUint::from(0b1101u32));
+    /// ```
+    pub fn bits_range(&self, range: Range<u64>) -> BigUint {
+        assert!(range.start <= range.end, "range starts after it ends");
+        let bits_per_digit = u64::from(big_digit::BITS);
+        let end = range.end.min(self.bits());
+        if range.start >= end {
+            return Self::ZERO;
+        }
+        let lo = (range.start / bits_per_digit) as usize;
+        let hi = Integer::div_ceil(&end, &bits_per_digit) as usize;
+        let mut value =
+            biguint_from_vec(self.data[lo..hi].to_vec()) >> (range.start % bits_per_digit);
+        value.clear_bits_from(end - range.start);
+        value
+    }
+
+    /// Returns the `len` bits of `self` starting at bit `start`, as a `u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len > 64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from(0xabcd_ef01_2345_6789_u64) << 100u8;
+    /// assert_eq!(x.extract_bits_u64(116, 32), 0xef01_2345);
+    /// ```
+    pub fn extract_bits_u64(&self, start: u64, len: u32) -> u64 {
+        assert!(len <= 64, "cannot extract more than 64 bits");
+        if len == 0 {
+            return 0;
+        }
+        let bits_per_digit = u64::from(big_digit::BITS);
+        let end = start.saturating_add(u64::from(len));
+        let mut value = 0;
+        for (i, _) in bits::range_masks(start, end) {
+            let Some(&digit) = self.data.get(i) else {
+                break;
+            };
+            #[allow(clippy::useless_conversion)]
+            let digit = u64::from(digit);
+            let offset = i as u64 * bits_per_digit;
+            if offset >= start {
+                value |= digit << (offset - start);
+            } else {
+                value |= digit >> (start - offset);
+            }
+        }
+        if len < 64 {
+            value &= (1 << len) - 1;
+        }
+        value
+    }
+
+    /// Returns an iterator over the bits of `self`, from the least significant up to the most
+    /// significant one bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let bits: Vec<bool> = BigUint::from(6u32).iter_bits().collect();
+    /// assert_eq!(bits, [false, true, true]);
+    /// ```
+    pub fn iter_bits(&self) -> IterBits<'_> {
+        IterBits::new(&self.data, self.bits())
+    }
+
+    /// Returns an iterator over the positions of the one bits of `self`, in increasing order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let ones: Vec<u64> = (BigUint::from(5u32) << 100u8).iter_ones().collect();
+    /// assert_eq!(ones, [100, 102]);
+    /// ```
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes::new(&self.data)
+    }
+
+    /// Sets or clears all the bits in `range`.
+    ///
+    /// As with [`set_bit`](Self::set_bit), setting bits beyond the current bit length may need
+    /// a reallocation to store the new digits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range.start > range.end`.
+    pub fn set_bits_range(&mut self, range: Range<u64>, value: bool) {
+        assert!(range.start <= range.end, "range starts after it ends");
+        let bits_per_digit = u64::from(big_digit::BITS);
+        let mut end = range.end;
+        if range.start == end {
+            return;
+        }
+        if value {
+            // saturating as in `set_bit`, as such a length is bound to fail allocation
+            let new_len = Integer::div_ceil(&end, &bits_per_digit)
+                .to_usize()
+                .unwrap_or(usize::MAX);
+            if new_len > self.data.len() {
+                self.data.resize(new_len, 0);
+            }
+        } else {
+            end = end.min(self.data.len() as u64 * bits_per_digit);
+            if range.start >= end {
+                return;
+            }
+        }
+        for (i, mask) in bits::range_masks(range.start, end) {
+            if value {
+                self.data[i] |= mask;
+            } else {
+                self.data[i] &= !mask;
+            }
+        }
+        if !value {
+            self.normalize();
+        }
+    }
+
+    /// Clears all the bits from position `bit` up, leaving `self` modulo `2^bit`.
+    pub fn clear_bits_from(&mut self, bit: u64) {
+        let bits_per_digit = u64::from(big_digit::BITS);
+        if let Some(digit_index) = (bit / bits_per_digit).to_usize() {
+            if digit_index < self.data.len() {
+                self.data.truncate(digit_index + 1);
+                self.data[digit_index] &= !(big_digit::MAX << (bit % bits_per_digit));
+                self.normalize();
+            }
+        }
+    }
+
+    /// Returns the complement of the low `bits` bits of `self`, that is `self ^ (2^bits - 1)`
+    /// for `self` below `2^bits`, with any higher bits of `self` dropped.
+    ///
+    /// `BigUint` has no `Not`, since the complement of an unsigned number has infinitely many
+    /// one bits. This takes the complement within a given width instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::BigUint;
+    ///
+    /// let x = BigUint::from(0b1010u32);
+    /// assert_eq!(x.not_bits(4), BigUint::from(0b0101u32));
+    /// assert_eq!(x.not_bits(8), BigUint::from(0b1111_0101u32));
+    /// assert_eq!(x.not_bits(2), BigUint::from(0b01u32));
+    /// ```
+    pub fn not_bits(&self, bits: u64) -> BigUint {
+        let len = Integer::div_ceil(&bits, &u64::from(big_digit::BITS))
+            .to_usize()
+            .expect("capacity overflow");
+        let mut data: Vec<BigDigit> = self.data.iter().take(len).map(|&d| !d).collect();
+        data.resize(len, big_digit::MAX);
+        let mut x = biguint_from_vec(data);
+        x.clear_bits_from(bits);
+        x
+    }
+
+    /// Returns the number of one bits in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range.start > range.end`.
+    pub fn count_ones_range(&self, range: Range<u64>) -> u64 {
+        assert!(range.start <= range.end, "range starts after it ends");
+        let end = range.end.min(self.bits());
+        if range.start >= end {
+            return 0;
+        }
+        bits::range_masks(range.start, end)
+            .map(|(i, mask)| u64::from((self.data[i] & mask).count_ones()))
+            .sum()
+    }
+
+    /// Returns the number of bit positions at which `self` and `other` differ.
+    pub fn hamming_distance(&self, other: &BigUint) -> u64 {
+        let (short, long) = if self.data.len() <= other.data.len() {
+            (&self.data, &other.data)
+        } else {
+            (&other.data, &self.data)
+        };
+        let common: u64 = short
+            .iter()
+            .zip(long.iter())
+            .map(|(&a, &b)| u64::from((a ^ b).count_ones()))
+            .sum();
+        let rest: u64 = long[short.len()..]
+            .iter()
+            .map(|&d| u64::from(d.count_ones()))
+            .sum();
+        common + rest
+    }
 }
 
 impl num_traits::FromBytes for BigUint {
@@ -1066,8 +1582,8 @@ impl num_traits::ToBytes for BigUint {
 
 pub(crate) trait IntDigits {
     fn digits(&self) -> &[BigDigit];
-    // fn digits_mut(&mut self) -> &mut Vec<BigDigit>;
-    // fn normalize(&mut self);
+    fn digits_mut(&mut self) -> &mut Vec<BigDigit>;
+    fn normalize(&mut self);
     fn capacity(&self) -> usize;
     fn len(&self) -> usize;
 }
@@ -1077,14 +1593,14 @@ impl IntDigits for BigUint {
     fn digits(&self) -> &[BigDigit] {
         &self.data
     }
-    // #[inline]
-    // fn digits_mut(&mut self) -> &mut Vec<BigDigit> {
-    //     &mut self.data
-    // }
-    // #[inline]
-    // fn normalize(&mut self) {
-    //     self.normalize();
-    // }
+    #[inline]
+    fn digits_mut(&mut self) -> &mut Vec<BigDigit> {
+        &mut self.data
+    }
+    #[inline]
+    fn normalize(&mut self) {
+        self.normalize();
+    }
     #[inline]
     fn capacity(&self) -> usize {
         self.data.capacity()
@@ -1095,16 +1611,18 @@ impl IntDigits for BigUint {
     }
 }
 
-/// Convert a `u32` chunk (len is either 1 or 2) to a single `u64` digit
-#[inline]
-fn u32_chunk_to_u64(chunk: &[u32]) -> u64 {
-    // raw could have odd length
-    let mut digit = chunk[0] as u64;
-    if let Some(&hi) = chunk.get(1) {
-        digit |= (hi as u64) << 32;
+cfg_64!(
+    /// Convert a `u32` chunk (len is either 1 or 2) to a single `u64` digit
+    #[inline]
+    fn u32_chunk_to_u64(chunk: &[u32]) -> u64 {
+        // raw could have odd length
+        let mut digit = chunk[0] as u64;
+        if let Some(&hi) = chunk.get(1) {
+            digit |= (hi as u64) << 32;
+        }
+        digit
     }
-    digit
-}
+);
 
 cfg_32_or_test!(
     /// Combine four `u32`s into a single `u128`.
@@ -1127,6 +1645,36 @@ cfg_32_or_test!(
     }
 );
 
+/// A seeded xorshift generator of random operands for the tests, so that failures reproduce.
+#[cfg(test)]
+pub(crate) struct TestRng(u64);
+
+#[cfg(test)]
+impl TestRng {
+    /// The seed must be nonzero.
+    pub(crate) fn new(seed: u64) -> Self {
+        debug_assert_ne!(seed, 0);
+        TestRng(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns `len` random digits.
+    pub(crate) fn digits(&mut self, len: usize) -> Vec<BigDigit> {
+        (0..len).map(|_| self.next_u64() as BigDigit).collect()
+    }
+
+    /// Returns `len` random `u64` words, whatever the digit size.
+    pub(crate) fn words(&mut self, len: usize) -> Vec<u64> {
+        (0..len).map(|_| self.next_u64()).collect()
+    }
+}
+
 cfg_digit!(
     #[test]
     fn test_from_slice() {
@@ -52,7 +52,7 @@ fn adc(carry: u8, lhs: BigDigit, rhs: BigDigit, out: &mut BigDigit) -> u8 {
 ///
 /// The caller _must_ ensure that `a` is at least as long as `b`.
 #[inline]
-pub(super) fn __add2(a: &mut [BigDigit], b: &[BigDigit]) -> BigDigit {
+pub(crate) fn __add2(a: &mut [BigDigit], b: &[BigDigit]) -> BigDigit {
     debug_assert!(a.len() >= b.len());
 
     let mut carry = 0;
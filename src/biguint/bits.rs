@@ -0,0 +1,368 @@
+use super::{BigUint, IntDigits};
+
+use crate::big_digit::{self, BigDigit};
+use crate::UsizePromotion;
+
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+
+// There is deliberately no `Not` for `BigUint`: the complement of an unsigned number has
+// infinitely many one bits. `BigUint::not_bits` complements within a given width instead.
+
+fn bitand_digits(a: &mut BigUint, b: &[BigDigit]) {
+    for (ai, &bi) in a.data.iter_mut().zip(b.iter()) {
+        *ai &= bi;
+    }
+    a.data.truncate(b.len());
+    a.normalize();
+}
+
+fn bitor_digits(a: &mut BigUint, b: &[BigDigit]) {
+    for (ai, &bi) in a.data.iter_mut().zip(b.iter()) {
+        *ai |= bi;
+    }
+    if b.len() > a.data.len() {
+        let extra = &b[a.data.len()..];
+        a.data.extend(extra.iter().cloned());
+        a.normalize();
+    }
+}
+
+fn bitxor_digits(a: &mut BigUint, b: &[BigDigit]) {
+    for (ai, &bi) in a.data.iter_mut().zip(b.iter()) {
+        *ai ^= bi;
+    }
+    if b.len() > a.data.len() {
+        let extra = &b[a.data.len()..];
+        a.data.extend(extra.iter().cloned());
+    }
+    a.normalize();
+}
+
+/// Returns the index of each digit overlapping the non-empty bit range `start..end`, with a mask
+/// of the bits of the digit within the range.
+pub(super) fn range_masks(start: u64, end: u64) -> impl Iterator<Item = (usize, BigDigit)> {
+    debug_assert!(start < end);
+    let bits_per_digit = u64::from(big_digit::BITS);
+    let lo = start / bits_per_digit;
+    let hi = (end - 1) / bits_per_digit;
+    (lo..=hi).map(move |i| {
+        let mut mask = big_digit::MAX;
+        if i == lo {
+            mask &= big_digit::MAX << (start % bits_per_digit);
+        }
+        if i == hi {
+            mask &= big_digit::MAX >> (bits_per_digit - 1 - (end - 1) % bits_per_digit);
+        }
+        (i as usize, mask)
+    })
+}
+
+#[inline]
+fn u32_digits(n: u32) -> [BigDigit; 1] {
+    [n as BigDigit]
+}
+
+cfg_digit!(
+    #[inline]
+    fn u64_digits(n: u64) -> [BigDigit; 2] {
+        let (hi, lo) = big_digit::from_doublebigdigit(n);
+        [lo, hi]
+    }
+
+    #[inline]
+    fn u64_digits(n: u64) -> [BigDigit; 1] {
+        [n]
+    }
+);
+
+cfg_digit!(
+    #[inline]
+    fn u128_digits(n: u128) -> [BigDigit; 4] {
+        let (a, b, c, d) = super::u32_from_u128(n);
+        [d, c, b, a]
+    }
+
+    #[inline]
+    fn u128_digits(n: u128) -> [BigDigit; 2] {
+        let (hi, lo) = big_digit::from_doublebigdigit(n);
+        [lo, hi]
+    }
+);
+
+forward_val_val_binop!(impl BitAnd for BigUint, bitand);
+forward_ref_val_binop!(impl BitAnd for BigUint, bitand);
+
+// do not use forward_ref_ref_binop_commutative! for bitand so that we can
+// clone the smaller value rather than the larger, avoiding over-allocation
+impl BitAnd<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    #[inline]
+    fn bitand(self, other: &BigUint) -> BigUint {
+        // forward to val-ref, choosing the smaller to clone
+        if self.data.len() <= other.data.len() {
+            self.clone() & other
+        } else {
+            other.clone() & self
+        }
+    }
+}
+
+forward_val_assign!(impl BitAndAssign for BigUint, bitand_assign);
+
+impl BitAnd<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    #[inline]
+    fn bitand(mut self, other: &BigUint) -> BigUint {
+        self &= other;
+        self
+    }
+}
+impl BitAndAssign<&BigUint> for BigUint {
+    #[inline]
+    fn bitand_assign(&mut self, other: &BigUint) {
+        bitand_digits(self, &other.data);
+    }
+}
+
+forward_all_binop_to_val_ref_commutative!(impl BitOr for BigUint, bitor);
+forward_val_assign!(impl BitOrAssign for BigUint, bitor_assign);
+
+impl BitOr<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn bitor(mut self, other: &BigUint) -> BigUint {
+        self |= other;
+        self
+    }
+}
+impl BitOrAssign<&BigUint> for BigUint {
+    #[inline]
+    fn bitor_assign(&mut self, other: &BigUint) {
+        bitor_digits(self, &other.data);
+    }
+}
+
+forward_all_binop_to_val_ref_commutative!(impl BitXor for BigUint, bitxor);
+forward_val_assign!(impl BitXorAssign for BigUint, bitxor_assign);
+
+impl BitXor<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn bitxor(mut self, other: &BigUint) -> BigUint {
+        self ^= other;
+        self
+    }
+}
+impl BitXorAssign<&BigUint> for BigUint {
+    #[inline]
+    fn bitxor_assign(&mut self, other: &BigUint) {
+        bitxor_digits(self, &other.data);
+    }
+}
+
+promote_unsigned_scalars!(impl BitAnd for BigUint, bitand);
+promote_unsigned_scalars_assign!(impl BitAndAssign for BigUint, bitand_assign);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitAnd<u32> for BigUint, bitand);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitAnd<u64> for BigUint, bitand);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitAnd<u128> for BigUint, bitand);
+
+promote_unsigned_scalars!(impl BitOr for BigUint, bitor);
+promote_unsigned_scalars_assign!(impl BitOrAssign for BigUint, bitor_assign);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitOr<u32> for BigUint, bitor);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitOr<u64> for BigUint, bitor);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitOr<u128> for BigUint, bitor);
+
+promote_unsigned_scalars!(impl BitXor for BigUint, bitxor);
+promote_unsigned_scalars_assign!(impl BitXorAssign for BigUint, bitxor_assign);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitXor<u32> for BigUint, bitxor);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitXor<u64> for BigUint, bitxor);
+forward_all_scalar_binop_to_val_val_commutative!(impl BitXor<u128> for BigUint, bitxor);
+
+macro_rules! impl_scalar_bitop {
+    ($scalar:ty, $to_digits:ident) => {
+        impl BitAnd<$scalar> for BigUint {
+            type Output = BigUint;
+
+            #[inline]
+            fn bitand(mut self, other: $scalar) -> BigUint {
+                self &= other;
+                self
+            }
+        }
+        impl BitAndAssign<$scalar> for BigUint {
+            #[inline]
+            fn bitand_assign(&mut self, other: $scalar) {
+                bitand_digits(self, &$to_digits(other));
+            }
+        }
+
+        impl BitOr<$scalar> for BigUint {
+            type Output = BigUint;
+
+            #[inline]
+            fn bitor(mut self, other: $scalar) -> BigUint {
+                self |= other;
+                self
+            }
+        }
+        impl BitOrAssign<$scalar> for BigUint {
+            #[inline]
+            fn bitor_assign(&mut self, other: $scalar) {
+                bitor_digits(self, &$to_digits(other));
+            }
+        }
+
+        impl BitXor<$scalar> for BigUint {
+            type Output = BigUint;
+
+            #[inline]
+            fn bitxor(mut self, other: $scalar) -> BigUint {
+                self ^= other;
+                self
+            }
+        }
+        impl BitXorAssign<$scalar> for BigUint {
+            #[inline]
+            fn bitxor_assign(&mut self, other: $scalar) {
+                bitxor_digits(self, &$to_digits(other));
+            }
+        }
+    };
+}
+
+impl_scalar_bitop!(u32, u32_digits);
+impl_scalar_bitop!(u64, u64_digits);
+impl_scalar_bitop!(u128, u128_digits);
+
+#[test]
+fn test_bitops() {
+    let a = BigUint::new(vec![0xf0f0_f0f0, 0x1234_5678, 0xffff_ffff, 1]);
+    let b = BigUint::new(vec![0x0ff0_0ff0, 0xffff_ffff]);
+    let c = BigUint::from(0x8000_0000_0000_0000_0000_0000_0000_0001u128);
+
+    assert_eq!(&a & &b, BigUint::new(vec![0x00f0_00f0, 0x1234_5678]));
+    assert_eq!(
+        &a | &b,
+        BigUint::new(vec![0xfff0_fff0, 0xffff_ffff, 0xffff_ffff, 1])
+    );
+    assert_eq!(
+        &a ^ &b,
+        BigUint::new(vec![0xff00_ff00, 0xedcb_a987, 0xffff_ffff, 1])
+    );
+    assert_eq!(&a ^ &a, BigUint::ZERO);
+    assert_eq!(&b & &c, BigUint::from(0u32));
+    assert_eq!(&b | &c, &b + &c);
+
+    for x in [&a, &b, &c] {
+        let d = x.digits();
+        for y in [&a, &b, &c] {
+            let e = y.digits();
+            let and: BigUint = x.clone() & y;
+            let or = x | y.clone();
+            let xor = x.clone() ^ y.clone();
+            assert_eq!(and, y & x);
+            assert_eq!(&or - &and, xor);
+            assert!(and.len() <= Ord::min(d.len(), e.len()));
+
+            let mut assign = x.clone();
+            assign &= y;
+            assign |= y.clone();
+            assert_eq!(assign, y | (x & y));
+        }
+    }
+
+    for n in [
+        0u128,
+        1,
+        0xffff_ffff,
+        0x1_0000_0000,
+        u64::MAX.into(),
+        u128::MAX,
+    ] {
+        let big = BigUint::from(n);
+        for x in [&a, &b, &c] {
+            assert_eq!(x & n, x & &big);
+            assert_eq!(n | x, x | &big);
+            assert_eq!(x.clone() ^ n, x ^ &big);
+            if let Ok(n) = u64::try_from(n) {
+                assert_eq!(x & n, x & &big);
+                assert_eq!(n ^ x.clone(), x ^ &big);
+            }
+            if let Ok(n) = u32::try_from(n) {
+                let mut y = x.clone();
+                y |= n;
+                assert_eq!(y, x | &big);
+                assert_eq!(x & (n as u8), x & (n as u8 as u32));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bit_ranges() {
+    use alloc::vec::Vec;
+
+    let x = BigUint::new(vec![0xf0f0_f0f0, 0x1234_5678, 0, 0xffff_ffff, 0x8000_0001]);
+    let y = BigUint::new(vec![0x0ff0_0ff0, 0xffff_ffff, 0x5555_5555]);
+    let naive = |x: &BigUint, lo: u64, hi: u64| -> Vec<u64> {
+        (lo..hi).filter(|&i| x.bit(i)).map(|i| i - lo).collect()
+    };
+    let bounds = [
+        0, 1, 4, 31, 32, 33, 63, 64, 65, 100, 127, 128, 159, 160, 200,
+    ];
+
+    for &lo in &bounds {
+        for &hi in bounds.iter().filter(|&&hi| hi >= lo) {
+            let ones = naive(&x, lo, hi);
+            let range = x.bits_range(lo..hi);
+            assert_eq!(
+                range.iter_ones().collect::<Vec<_>>(),
+                ones,
+                "{}..{}",
+                lo,
+                hi
+            );
+            assert_eq!(x.count_ones_range(lo..hi), ones.len() as u64);
+            if hi - lo <= 64 {
+                assert_eq!(
+                    BigUint::from(x.extract_bits_u64(lo, (hi - lo) as u32)),
+                    range
+                );
+            }
+
+            for value in [false, true] {
+                let mut z = x.clone();
+                z.set_bits_range(lo..hi, value);
+                for i in 0..220 {
+                    let expected = if (lo..hi).contains(&i) {
+                        value
+                    } else {
+                        x.bit(i)
+                    };
+                    assert_eq!(z.bit(i), expected);
+                }
+                assert!(z.digits().last() != Some(&0));
+            }
+        }
+
+        let mut z = x.clone();
+        z.clear_bits_from(lo);
+        assert_eq!(z, x.bits_range(0..lo));
+    }
+
+    assert_eq!(x.hamming_distance(&y), (&x ^ &y).count_ones());
+    assert_eq!(y.hamming_distance(&x), (&x ^ &y).count_ones());
+    assert_eq!(x.hamming_distance(&x), 0);
+    assert_eq!(x.hamming_distance(&BigUint::ZERO), x.count_ones());
+
+    for bits in [0u64, 1, 31, 32, 33, 64, 100, 200] {
+        let mask = (BigUint::from(1u32) << bits) - 1u32;
+        for v in [&x, &y] {
+            let low = v & &mask;
+            assert_eq!(v.not_bits(bits), &low ^ &mask);
+            assert_eq!(v.not_bits(bits).not_bits(bits), low);
+        }
+    }
+}
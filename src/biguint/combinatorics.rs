@@ -0,0 +1,233 @@
+//! Factorials, binomials and related products.
+//!
+//! Rather than multiplying the terms one at a time, each value is assembled from its prime
+//! factorisation: the exponent of every prime follows from Legendre's formula, and the primes are
+//! combined by exponent bit, so that `x = ∏ P_b^(2^b)` where `P_b` is the product of the primes
+//! whose exponent has bit `b` set. Each `P_b` is a balanced product tree, whose subtrees are
+//! evaluated concurrently on the executor once they are large enough.
+//!
+//! Sieving the primes up to `n` costs time and memory linear in `n`, so a binomial with a small
+//! `k` is instead the product of its `k` top terms, divided exactly by `k!`.
+
+use alloc::vec::Vec;
+
+use num_integer::Integer;
+use num_traits::One;
+
+use super::division::div_exact;
+use super::sieve::primes_up_to;
+use super::BigUint;
+
+use crate::executor;
+
+/// Factors at the leaves of the product tree are multiplied in one at a time.
+const LEAF_LEN: usize = 16;
+
+/// A binomial `C(n, k)` with `k` at most `n / SIEVE_RATIO` is computed from its terms rather
+/// than from the primes up to `n`.
+const SIEVE_RATIO: u64 = 16;
+
+/// Returns the product of all `factors`, using a balanced product tree.
+pub(super) fn product(factors: &[u64]) -> BigUint {
+    let parallel = executor::split_count(factors.len()) > 1;
+    let p = product_tree(factors, parallel);
+    if parallel {
+        executor::verify("product tree", &p, || product_tree(factors, false), factors);
+    }
+    p
+}
+
+fn product_tree(factors: &[u64], parallel: bool) -> BigUint {
+    if factors.len() <= LEAF_LEN {
+        let mut p = BigUint::one();
+        for &f in factors {
+            p *= f;
+        }
+        return p;
+    }
+
+    let (lo, hi) = factors.split_at(factors.len() / 2);
+    let (a, b) = if parallel && executor::split_count(factors.len()) > 1 {
+        executor::join(|| product_tree(lo, true), || product_tree(hi, true))
+    } else {
+        (product_tree(lo, false), product_tree(hi, false))
+    };
+    a * b
+}
+
+/// The exponent of the prime `p` in `n!`, by Legendre's formula.
+fn legendre(mut n: u64, p: u64) -> u64 {
+    let mut e = 0;
+    while n >= p {
+        n /= p;
+        e += n;
+    }
+    e
+}
+
+/// Returns `∏ p^exponent(p)` over the given primes.
+fn from_exponents<F>(primes: &[u64], exponent: F) -> BigUint
+where
+    F: Fn(u64) -> u64,
+{
+    let mut twos = 0;
+    let mut levels: Vec<Vec<u64>> = Vec::new();
+    for &p in primes {
+        let e = exponent(p);
+        if p == 2 {
+            twos = e;
+            continue;
+        }
+        let bits = (u64::BITS - e.leading_zeros()) as usize;
+        if levels.len() < bits {
+            levels.resize(bits, Vec::new());
+        }
+        for (b, level) in levels.iter_mut().enumerate().take(bits) {
+            if (e >> b) & 1 == 1 {
+                level.push(p);
+            }
+        }
+    }
+
+    let mut x = BigUint::one();
+    for level in levels.iter().rev() {
+        x = x.square();
+        x *= product(level);
+    }
+    x << twos
+}
+
+pub(super) fn factorial(n: u64) -> BigUint {
+    from_exponents(&primes_up_to(n), |p| legendre(n, p))
+}
+
+pub(super) fn double_factorial(n: u64) -> BigUint {
+    if n.is_even() {
+        // (2k)!! = 2^k k!
+        factorial(n / 2) << (n / 2)
+    } else {
+        // n! = n!! (n - 1)!!, where (n - 1)!! = 2^k k!, and n!! has no factors of two.
+        let k = n / 2;
+        from_exponents(&primes_up_to(n), |p| {
+            if p == 2 {
+                0
+            } else {
+                legendre(n, p) - legendre(k, p)
+            }
+        })
+    }
+}
+
+pub(super) fn binomial(n: u64, k: u64) -> BigUint {
+    if k > n {
+        return BigUint::ZERO;
+    }
+    let k = k.min(n - k);
+    if k <= n / SIEVE_RATIO {
+        // n (n - 1) ... (n - k + 1) / k!
+        let terms: Vec<u64> = (n - k + 1..=n).collect();
+        return div_exact(&product(&terms), &factorial(k));
+    }
+    from_exponents(&primes_up_to(n), |p| {
+        legendre(n, p) - legendre(k, p) - legendre(n - k, p)
+    })
+}
+
+pub(super) fn multinomial(ks: &[u64]) -> BigUint {
+    let n = ks
+        .iter()
+        .try_fold(0u64, |n, &k| n.checked_add(k))
+        .expect("multinomial total overflows u64");
+    // Splitting off the largest part leaves C(n, rest) times the multinomial of the other parts,
+    // which only sieves up to `rest`, when the rest is small.
+    if let Some((i, &largest)) = ks.iter().enumerate().max_by_key(|&(_, &k)| k) {
+        let rest = n - largest;
+        if rest <= n / SIEVE_RATIO {
+            let others: Vec<u64> = [&ks[..i], &ks[i + 1..]].concat();
+            return binomial(n, rest) * multinomial(&others);
+        }
+    }
+    from_exponents(&primes_up_to(n), |p| {
+        ks.iter().fold(legendre(n, p), |e, &k| e - legendre(k, p))
+    })
+}
+
+pub(super) fn primorial(n: u64) -> BigUint {
+    product(&primes_up_to(n))
+}
+
+#[test]
+fn test_factorials() {
+    let mut f = BigUint::one();
+    for n in 0..300u64 {
+        if n > 0 {
+            f *= n;
+        }
+        assert_eq!(factorial(n), f, "{}!", n);
+    }
+
+    let mut odd = BigUint::one();
+    let mut even = BigUint::one();
+    for n in 0..300u64 {
+        let df = if n % 2 == 0 {
+            if n > 0 {
+                even *= n;
+            }
+            &even
+        } else {
+            odd *= n;
+            &odd
+        };
+        assert_eq!(double_factorial(n), *df, "{}!!", n);
+    }
+}
+
+#[test]
+fn test_binomials() {
+    let mut row = vec![BigUint::one()];
+    for n in 0..200u64 {
+        for (k, c) in row.iter().enumerate() {
+            assert_eq!(binomial(n, k as u64), *c, "C({}, {})", n, k);
+        }
+        assert_eq!(binomial(n, n + 1), BigUint::ZERO);
+
+        let mut next = vec![BigUint::one(); row.len() + 1];
+        for k in 1..row.len() {
+            next[k] = &row[k - 1] + &row[k];
+        }
+        row = next;
+    }
+
+    assert_eq!(multinomial(&[]), BigUint::one());
+    assert_eq!(multinomial(&[7]), BigUint::one());
+    assert_eq!(multinomial(&[3, 4]), binomial(7, 3));
+    assert_eq!(multinomial(&[2, 3, 4]), BigUint::from(1260u32));
+    assert_eq!(multinomial(&[0, 5, 0, 1]), BigUint::from(6u32));
+    assert_eq!(
+        multinomial(&[10, 20, 30]),
+        binomial(60, 10) * binomial(50, 20)
+    );
+
+    // A tiny k must not sieve the primes up to a huge n.
+    let n = 1_000_000_000_000u64;
+    let big = BigUint::from(n);
+    assert_eq!(binomial(n, 0), BigUint::one());
+    assert_eq!(binomial(n, n - 1), big);
+    assert_eq!(binomial(n, 3), &big * (&big - 1u32) * (&big - 2u32) / 6u32);
+    let total = &big + 3u32;
+    assert_eq!(
+        multinomial(&[2, n, 1]),
+        &total * (&total - 1u32) * (&total - 2u32) / 2u32
+    );
+}
+
+#[test]
+fn test_primorial() {
+    assert_eq!(primorial(0), BigUint::one());
+    assert_eq!(primorial(1), BigUint::one());
+    assert_eq!(primorial(30), BigUint::from(6_469_693_230u64));
+    let product = primes_up_to(1000)
+        .iter()
+        .fold(BigUint::one(), |acc, &p| acc * p);
+    assert_eq!(primorial(1000), product);
+}
@@ -4,10 +4,11 @@
 use super::{biguint_from_vec, BigUint, ToBigUint};
 
 use super::addition::add2;
-use super::division::{div_rem_digit, FAST_DIV_WIDE};
+use super::divisor::DigitDivisor;
 use super::multiplication::mac_with_carry;
 
 use crate::big_digit::{self, BigDigit};
+use crate::tuning;
 use crate::ParseBigIntError;
 use crate::TryFromBigIntError;
 
@@ -687,20 +688,18 @@ pub(super) fn to_radix_digits_le(u: &BigUint, radix: u32) -> Vec<u8> {
 
     let mut digits = u.clone();
 
-    // X86 DIV can quickly divide by a full digit, otherwise we choose a divisor
-    // that's suitable for `div_half` to avoid slow `DoubleBigDigit` division.
-    let (base, power) = if FAST_DIV_WIDE {
-        get_radix_base(radix)
-    } else {
-        get_half_radix_base(radix)
-    };
-    let radix = radix as BigDigit;
+    // Dividing by a precomputed reciprocal doesn't need a hardware division, so we can use the
+    // largest power of the radix that fits in a digit on every target.
+    let (base, power) = get_radix_base(radix);
+    #[allow(clippy::useless_conversion)]
+    let divisor = DigitDivisor::new(u64::from(base));
+    let radix = u64::from(radix);
 
-    // For very large numbers, the O(n²) loop of repeated `div_rem_digit` dominates the
+    // For very large numbers, the O(n²) loop of repeated single-digit division dominates the
     // performance. We can mitigate this by dividing into chunks of a larger base first.
-    // The threshold for this was chosen by anecdotal performance measurements to
+    // The default threshold for this was chosen by anecdotal performance measurements to
     // approximate where this starts to make a noticeable difference.
-    if digits.data.len() >= 64 {
+    if digits.data.len() >= tuning::radix_divide_threshold() {
         let mut big_base = BigUint::from(base);
         let mut big_power = 1usize;
 
@@ -719,8 +718,7 @@ pub(super) fn to_radix_digits_le(u: &BigUint, radix: u32) -> Vec<u8> {
 
             // This inner loop now has O(√n²)=O(n) behavior altogether.
             for _ in 0..big_power {
-                let (q, mut r) = div_rem_digit(big_r, base);
-                big_r = q;
+                let mut r = divisor.div_rem_assign(&mut big_r);
                 for _ in 0..power {
                     res.push((r % radix) as u8);
                     r /= radix;
@@ -730,15 +728,15 @@ pub(super) fn to_radix_digits_le(u: &BigUint, radix: u32) -> Vec<u8> {
     }
 
     while digits.data.len() > 1 {
-        let (q, mut r) = div_rem_digit(digits, base);
+        let mut r = divisor.div_rem_assign(&mut digits);
         for _ in 0..power {
             res.push((r % radix) as u8);
             r /= radix;
         }
-        digits = q;
     }
 
-    let mut r = digits.data[0];
+    #[allow(clippy::useless_conversion)]
+    let mut r = u64::from(digits.data[0]);
     while r != 0 {
         res.push((r % radix) as u8);
         r /= radix;
@@ -797,15 +795,6 @@ fn get_radix_base(radix: u32) -> (BigDigit, usize) {
     BASES[radix as usize]
 }
 
-/// Returns the greatest power of the radix for half the `BigDigit` bit size
-#[inline]
-fn get_half_radix_base(radix: u32) -> (BigDigit, usize) {
-    static BASES: [(BigDigit, usize); 257] = generate_radix_bases(big_digit::HALF);
-    debug_assert!(!radix.is_power_of_two());
-    debug_assert!((3..256).contains(&radix));
-    BASES[radix as usize]
-}
-
 /// Generate tables of the greatest power of each radix that is less that the given maximum. These
 /// are returned from `get_radix_base` to batch the multiplication/division of radix conversions on
 /// full `BigUint` values, operating on primitive integers as much as possible.
@@ -851,16 +840,3 @@ fn test_radix_bases() {
         }
     }
 }
-
-#[test]
-fn test_half_radix_bases() {
-    for radix in 3u32..256 {
-        if !radix.is_power_of_two() {
-            let (base, power) = get_half_radix_base(radix);
-            let radix = BigDigit::from(radix);
-            let power = u32::try_from(power).unwrap();
-            assert_eq!(base, radix.pow(power));
-            assert!(radix.pow(power + 1) > big_digit::HALF);
-        }
-    }
-}
@@ -1,9 +1,10 @@
 use super::addition::__add2;
-use super::{cmp_slice, BigUint};
+use super::{biguint_from_vec, cmp_slice, mul_high_digits, BigUint};
 
 use crate::big_digit::{self, BigDigit, DoubleBigDigit};
-use crate::UsizePromotion;
+use crate::{executor, tuning, UsizePromotion};
 
+use alloc::vec::Vec;
 use core::cmp::Ordering::{Equal, Greater, Less};
 use core::mem;
 use core::ops::{Div, DivAssign, Rem, RemAssign};
@@ -82,7 +83,7 @@ fn div_half(rem: BigDigit, digit: BigDigit, divisor: BigDigit) -> (BigDigit, Big
 }
 
 #[inline]
-pub(super) fn div_rem_digit(mut a: BigUint, b: BigDigit) -> (BigUint, BigDigit) {
+pub(crate) fn div_rem_digit(mut a: BigUint, b: BigDigit) -> (BigUint, BigDigit) {
     if b == 0 {
         panic!("attempt to divide by zero")
     }
@@ -107,7 +108,7 @@ pub(super) fn div_rem_digit(mut a: BigUint, b: BigDigit) -> (BigUint, BigDigit)
 }
 
 #[inline]
-fn rem_digit(a: &BigUint, b: BigDigit) -> BigDigit {
+pub(super) fn rem_digit(a: &BigUint, b: BigDigit) -> BigDigit {
     if b == 0 {
         panic!("attempt to divide by zero")
     }
@@ -132,7 +133,7 @@ fn rem_digit(a: &BigUint, b: BigDigit) -> BigDigit {
 /// Subtract a multiple.
 /// a -= b * c
 /// Returns a borrow (if a < b then borrow > 0).
-fn sub_mul_digit_same_len(a: &mut [BigDigit], b: &[BigDigit], c: BigDigit) -> BigDigit {
+pub(crate) fn sub_mul_digit_same_len(a: &mut [BigDigit], b: &[BigDigit], c: BigDigit) -> BigDigit {
     debug_assert!(a.len() == b.len());
 
     // carry is between -big_digit::MAX and 0, so to avoid overflow we store
@@ -197,9 +198,9 @@ fn div_rem(mut u: BigUint, mut d: BigUint) -> (BigUint, BigUint) {
 
     if shift == 0 {
         // no need to clone d
-        div_rem_core(u, &d.data)
+        div_rem_normalized(u, &d.data)
     } else {
-        let (q, r) = div_rem_core(u << shift, &(d << shift).data);
+        let (q, r) = div_rem_normalized(u << shift, &(d << shift).data);
         // renormalize the remainder
         (q, r >> shift)
     }
@@ -239,9 +240,9 @@ pub(super) fn div_rem_ref(u: &BigUint, d: &BigUint) -> (BigUint, BigUint) {
 
     if shift == 0 {
         // no need to clone d
-        div_rem_core(u.clone(), &d.data)
+        div_rem_normalized(u.clone(), &d.data)
     } else {
-        let (q, r) = div_rem_core(u << shift, &(d << shift).data);
+        let (q, r) = div_rem_normalized(u << shift, &(d << shift).data);
         // renormalize the remainder
         (q, r >> shift)
     }
@@ -342,6 +343,253 @@ fn div_rem_core(mut a: BigUint, b: &[BigDigit]) -> (BigUint, BigUint) {
     (q.normalized(), a)
 }
 
+/// Divides a by b, whose highest digit has its top bit set, choosing the algorithm by the lengths
+/// of the divisor and the quotient.
+pub(crate) fn div_rem_normalized(a: BigUint, b: &[BigDigit]) -> (BigUint, BigUint) {
+    debug_assert!(a.data.len() >= b.len() && b.len() > 1);
+    let n = Ord::min(b.len(), a.data.len() - b.len() + 1);
+    if n <= tuning::div_burnikel_ziegler_threshold() {
+        div_rem_core(a, b)
+    } else if n <= tuning::div_newton_threshold() {
+        div_rem_burnikel_ziegler(a, &BigUint { data: b.to_vec() })
+    } else {
+        div_rem_newton(a, &BigUint { data: b.to_vec() })
+    }
+}
+
+/// Returns the digits `lo..hi` of a, as a number of its own.
+pub(super) fn digits(a: &BigUint, lo: usize, hi: usize) -> BigUint {
+    let len = a.data.len();
+    biguint_from_vec(a.data[Ord::min(lo, len)..Ord::min(hi, len)].to_vec())
+}
+
+/// Returns a * B^n, where B is the base of a digit.
+pub(super) fn shl_digits(a: BigUint, n: usize) -> BigUint {
+    a << (n * big_digit::BITS as usize)
+}
+
+/// Like `div_rem_core`, but for any a, and a divisor of any length.
+fn div_rem_base(a: BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    if a < *b {
+        (BigUint::ZERO, a)
+    } else if b.data.len() == 1 {
+        let (q, r) = div_rem_digit(a, b.data[0]);
+        (q, r.into())
+    } else {
+        div_rem_core(a, &b.data)
+    }
+}
+
+/// Divides a by the n-digit b one block of n digits at a time, from the most significant end.
+///
+/// Each step divides the remainder so far, followed by the next block, by b. Since the remainder
+/// is less than b, so is the quotient of each step less than B^n, and `step` only has to handle
+/// dividends less than b * B^n.
+fn div_rem_blocks<F>(a: BigUint, b: &BigUint, mut step: F) -> (BigUint, BigUint)
+where
+    F: FnMut(BigUint) -> (BigUint, BigUint),
+{
+    let n = b.data.len();
+    let mut q = vec![0; a.data.len()];
+    let mut r = BigUint::ZERO;
+    for lo in (0..a.data.len()).step_by(n).rev() {
+        let hi = Ord::min(lo + n, a.data.len());
+        // Only the most significant block can be short, and r is still zero then.
+        let mut block = a.data[lo..hi].to_vec();
+        block.extend_from_slice(&r.data);
+        let (qi, ri) = step(biguint_from_vec(block));
+        q[lo..lo + qi.data.len()].copy_from_slice(&qi.data);
+        r = ri;
+    }
+    (biguint_from_vec(q), r)
+}
+
+/// Returns x * y. When both are long enough, the longer one is split in two halves, whose
+/// products with the other are independent and computed concurrently on the executor.
+fn mul_split(x: &BigUint, y: &BigUint) -> BigUint {
+    let (x, y) = if x.data.len() <= y.data.len() {
+        (x, y)
+    } else {
+        (y, x)
+    };
+    if executor::split_count(x.data.len()) <= 1 {
+        return x * y;
+    }
+
+    let h = y.data.len() / 2;
+    let (y0, y1) = (digits(y, 0, h), digits(y, h, usize::MAX));
+    let (p0, p1) = executor::join(|| x * y0, || x * y1);
+    let p = shl_digits(p1, h) + p0;
+    executor::verify("division product", &p, || x * y, (x, y));
+    p
+}
+
+/// Recursive division, by Burnikel and Ziegler, "Fast Recursive Division" (1998).
+///
+/// The dividend is split into blocks as long as the divisor, and each step divides a 2n-digit
+/// number by an n-digit one as two steps dividing a 3h-digit number by a 2h-digit one, with
+/// h = n / 2. Each of those only needs a recursive 2h-by-h division, by the top half of the
+/// divisor, and a multiplication by the bottom half to correct the estimate, so division costs
+/// about twice as much as multiplication.
+fn div_rem_burnikel_ziegler(a: BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    div_rem_blocks(a, b, |block| div_2n_1n(block, b))
+}
+
+/// Divides a by b, which has n digits and is normalized, where a < b * B^n.
+fn div_2n_1n(a: BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    let n = b.data.len();
+    if n <= tuning::div_burnikel_ziegler_threshold() {
+        return div_rem_base(a, b);
+    }
+    if n.is_odd() {
+        // Shift both up by a digit, so that the divisor splits into halves of equal length.
+        let (q, r) = div_2n_1n(shl_digits(a, 1), &shl_digits(b.clone(), 1));
+        return (q, r >> big_digit::BITS);
+    }
+
+    let h = n / 2;
+    let (b1, b2) = (digits(b, h, n), digits(b, 0, h));
+    let (q1, r) = div_3h_2h(digits(&a, n, usize::MAX), digits(&a, h, n), b, &b1, &b2);
+    let (q0, r) = div_3h_2h(r, digits(&a, 0, h), b, &b1, &b2);
+    (shl_digits(q1, h) + q0, r)
+}
+
+/// Divides a12 * B^h + a3 by b = b1 * B^h + b2, where b1 is normalized and has h digits, and
+/// a12 < b.
+fn div_3h_2h(
+    a12: BigUint,
+    a3: BigUint,
+    b: &BigUint,
+    b1: &BigUint,
+    b2: &BigUint,
+) -> (BigUint, BigUint) {
+    let h = b1.data.len();
+    let (mut q, r) = if a12.data.len() > h && a12.data[h..] == b1.data[..] {
+        // The quotient estimate a12 / b1 would be B^h or more, but the quotient is less.
+        let q = BigUint {
+            data: vec![big_digit::MAX; h],
+        };
+        let r = a12 - shl_digits(b1.clone(), h) + b1;
+        (q, r)
+    } else {
+        div_2n_1n(a12, b1)
+    };
+
+    // The estimate can only be too large, by at most 2.
+    let mut r = shl_digits(r, h) + a3;
+    let d = mul_split(&q, b2);
+    while r < d {
+        q -= 1u32;
+        r += b;
+    }
+    (q, r - d)
+}
+
+/// Division by multiplication with an approximate reciprocal of the divisor, which is found by
+/// Newton's iteration. Everything is done with full-size multiplications, or the high half of
+/// one for the quotient estimates, so this is faster than Burnikel–Ziegler division once those
+/// use number-theoretic transforms.
+fn div_rem_newton(a: BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    let n = b.data.len();
+    let inv = reciprocal(b);
+    div_rem_blocks(a, b, |block| {
+        // The top n + 1 digits of the block are enough to estimate the quotient to within a few
+        // units, and the remainder fixes it up.
+        let mut q = mul_high_digits(&digits(&block, n - 1, usize::MAX).data, &inv.data, n + 1);
+        let mut p = mul_split(&q, b);
+        while p > block {
+            q -= 1u32;
+            p -= b;
+        }
+        let mut r = block - p;
+        while r >= *b {
+            q += 1u32;
+            r -= b;
+        }
+        (q, r)
+    })
+}
+
+/// Returns B^(2n) / b to within a few units, for the normalized n-digit b.
+///
+/// A reciprocal x of the top h digits of b, scaled up, has about h correct digits, and one step
+/// of Newton's iteration, x' = x + x * (B^(2n) - b * x) / B^(2n), doubles that. So the
+/// reciprocal costs a few multiplications of the full size, plus the recursive reciprocal of
+/// half the size.
+fn reciprocal(b: &BigUint) -> BigUint {
+    let n = b.data.len();
+    if n <= 4 || n <= tuning::div_newton_threshold() {
+        let (x, _) = div_rem_burnikel_ziegler(shl_digits(BigUint::one(), 2 * n), b);
+        return x;
+    }
+
+    // Two more digits than half keep the error within a few units.
+    let h = n / 2 + 2;
+    let x = shl_digits(reciprocal(&digits(b, n - h, n)), n - h);
+    let one = shl_digits(BigUint::one(), 2 * n);
+    let bx = mul_split(b, &x);
+    if bx <= one {
+        let e = mul_split(&x, &(one - bx)) >> (2 * n * big_digit::BITS as usize);
+        x + e
+    } else {
+        let e = mul_split(&x, &(bx - one)) >> (2 * n * big_digit::BITS as usize);
+        x - e
+    }
+}
+
+/// Exact division, by Jebelean, "An algorithm for exact division" (1993).
+///
+/// When d divides a, the quotient is determined by the low digits of a alone. After removing
+/// their common factors of two, d is odd and so has an inverse modulo B, and each digit of the
+/// quotient, from the least significant one up, is the low digit of what remains of a times that
+/// inverse. Digits of a beyond the length of the quotient are never needed.
+pub(super) fn div_exact(a: &BigUint, d: &BigUint) -> BigUint {
+    if d.is_zero() {
+        panic!("attempt to divide by zero")
+    }
+    let shift = d.trailing_zeros().unwrap();
+    let (a, d) = (a >> shift, d >> shift);
+    if a.data.len() < d.data.len() {
+        return BigUint::ZERO;
+    }
+
+    let q_len = a.data.len() - d.data.len() + 1;
+    if Ord::min(q_len, d.data.len()) > tuning::div_burnikel_ziegler_threshold() {
+        // Recursive division is faster than this quadratic algorithm.
+        return div_rem(a, d).0;
+    }
+
+    // Newton's iteration for the inverse modulo B, doubling the number of correct low bits from
+    // the three that any odd number has as its own inverse modulo 8.
+    let d0 = d.data[0];
+    let mut inv = d0;
+    for _ in 0..5 {
+        inv = inv.wrapping_mul((2 as BigDigit).wrapping_sub(d0.wrapping_mul(inv)));
+    }
+    debug_assert_eq!(d0.wrapping_mul(inv), 1);
+
+    let mut r = a.data;
+    r.truncate(q_len);
+    let mut q = Vec::with_capacity(q_len);
+    for i in 0..q_len {
+        let qi = r[i].wrapping_mul(inv);
+        q.push(qi);
+
+        // r -= qi * d * B^i, modulo B^q_len.
+        let len = Ord::min(d.data.len(), q_len - i);
+        let mut borrow = sub_mul_digit_same_len(&mut r[i..i + len], &d.data[..len], qi);
+        for ri in &mut r[i + len..] {
+            if borrow == 0 {
+                break;
+            }
+            let (diff, overflow) = ri.overflowing_sub(borrow);
+            *ri = diff;
+            borrow = overflow.into();
+        }
+    }
+    biguint_from_vec(q)
+}
+
 forward_val_ref_binop!(impl Div for BigUint, div);
 forward_ref_val_binop!(impl Div for BigUint, div);
 forward_val_assign!(impl DivAssign for BigUint, div_assign);
@@ -702,3 +950,70 @@ impl Euclid for BigUint {
         self.div_rem(v)
     }
 }
+
+#[test]
+fn test_div_rem_recursive() {
+    use super::TestRng;
+
+    let mut rng = TestRng::new(0x9e37_79b9_7f4a_7c15);
+
+    for &(al, bl) in &[
+        (4, 2),
+        (130, 65),
+        (300, 299),
+        (600, 300),
+        (1000, 257),
+        (777, 130),
+    ] {
+        for all_ones in [false, true] {
+            let mut digits = |len| -> BigUint {
+                if all_ones {
+                    biguint_from_vec(vec![BigDigit::MAX; len])
+                } else {
+                    biguint_from_vec(rng.digits(len))
+                }
+            };
+            let a = digits(al);
+            let mut b = digits(bl);
+            *b.data.last_mut().unwrap() |= 1 << (big_digit::BITS - 1);
+
+            let expected = div_rem_core(a.clone(), &b.data);
+            assert_eq!(
+                div_rem_burnikel_ziegler(a.clone(), &b),
+                expected,
+                "{}/{}",
+                al,
+                bl
+            );
+            assert_eq!(div_rem_newton(a, &b), expected, "{}/{}", al, bl);
+        }
+    }
+}
+
+#[test]
+fn test_div_exact() {
+    use super::TestRng;
+
+    let mut rng = TestRng::new(0x2545_f491_4f6c_dd1d);
+
+    for &(ql, dl) in &[
+        (1, 1),
+        (5, 1),
+        (1, 5),
+        (10, 10),
+        (40, 3),
+        (3, 40),
+        (200, 150),
+    ] {
+        for zeros in [0, 1, 70] {
+            let mut digits = |len| biguint_from_vec(rng.digits(len));
+            let q = digits(ql);
+            let d = digits(dl) << zeros;
+            assert_eq!(div_exact(&(&q * &d), &d), q, "{}/{} << {}", ql, dl, zeros);
+        }
+    }
+    assert_eq!(
+        div_exact(&BigUint::ZERO, &BigUint::from(7u32)),
+        BigUint::ZERO
+    );
+}
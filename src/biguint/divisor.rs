@@ -0,0 +1,187 @@
+//! Division by a word-sized divisor that is reused many times.
+//!
+//! This uses the 2-by-1 division of Möller and Granlund, "Improved division by invariant
+//! integers" (2011). A reciprocal of the divisor is computed once, after which each step of the
+//! long division takes two multiplications and a few adjustments instead of a hardware division,
+//! which is slow on most CPUs and missing for 128-by-64 bits on many of them.
+
+use super::BigUint;
+
+/// A word-sized divisor with a precomputed reciprocal, for dividing many numbers by the same
+/// divisor.
+///
+/// # Examples
+///
+/// ```
+/// use rust_monty_parallel::{BigUint, DigitDivisor};
+///
+/// let ten = DigitDivisor::new(10);
+/// let n = BigUint::from(12345u32);
+/// assert_eq!(ten.div_rem(&n), (BigUint::from(1234u32), 5));
+/// assert_eq!(ten.rem(&n), 5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DigitDivisor {
+    d: u64,
+    /// The divisor shifted left until its top bit is set.
+    normalized: u64,
+    shift: u32,
+    /// `(2^128 - 1) / normalized - 2^64`
+    reciprocal: u64,
+}
+
+impl DigitDivisor {
+    /// Precomputes the reciprocal of `d`.
+    ///
+    /// Panics if `d` is zero.
+    pub fn new(d: u64) -> Self {
+        if d == 0 {
+            panic!("attempt to divide by zero")
+        }
+        let shift = d.leading_zeros();
+        let normalized = d << shift;
+        DigitDivisor {
+            d,
+            normalized,
+            shift,
+            reciprocal: (u128::MAX / u128::from(normalized)) as u64,
+        }
+    }
+
+    /// Returns the divisor.
+    #[inline]
+    pub fn divisor(&self) -> u64 {
+        self.d
+    }
+
+    /// Divides `r * 2^64 + u` by the divisor, where `r` is less than the divisor, returning the
+    /// quotient and remainder.
+    #[inline]
+    fn div_wide(&self, r: u64, u: u64) -> (u64, u64) {
+        debug_assert!(r < self.d);
+        // Shifting the dividend as much as the divisor leaves the quotient unchanged, and the top
+        // word still less than the divisor.
+        let n = ((u128::from(r) << 64) | u128::from(u)) << self.shift;
+        let (u1, u0) = ((n >> 64) as u64, n as u64);
+
+        let q = u128::from(self.reciprocal) * u128::from(u1) + n;
+        let (mut q1, q0) = (((q >> 64) as u64).wrapping_add(1), q as u64);
+        let mut r = u0.wrapping_sub(q1.wrapping_mul(self.normalized));
+        if r > q0 {
+            q1 = q1.wrapping_sub(1);
+            r = r.wrapping_add(self.normalized);
+        }
+        if r >= self.normalized {
+            q1 += 1;
+            r -= self.normalized;
+        }
+        (q1, r >> self.shift)
+    }
+
+    /// Divides the little-endian words of `x` by the divisor in place, returning the remainder.
+    pub fn div_rem_slice(&self, x: &mut [u64]) -> u64 {
+        let mut r = 0;
+        for u in x.iter_mut().rev() {
+            let (q, rem) = self.div_wide(r, *u);
+            *u = q;
+            r = rem;
+        }
+        r
+    }
+
+    /// Returns the remainder of the little-endian words of `x` divided by the divisor.
+    pub fn rem_slice(&self, x: &[u64]) -> u64 {
+        x.iter().rev().fold(0, |r, &u| self.div_wide(r, u).1)
+    }
+
+    /// Returns the quotient and remainder of `x` divided by the divisor.
+    pub fn div_rem(&self, x: &BigUint) -> (BigUint, u64) {
+        let mut q = x.clone();
+        let r = self.div_rem_assign(&mut q);
+        (q, r)
+    }
+
+    cfg_digit!(
+        /// Returns the remainder of `x` divided by the divisor.
+        pub fn rem(&self, x: &BigUint) -> u64 {
+            x.data.chunks(2).rev().fold(0, |r, c| {
+                let u = u64::from(c[0]) | u64::from(c.get(1).copied().unwrap_or(0)) << 32;
+                self.div_wide(r, u).1
+            })
+        }
+
+        /// Returns the remainder of `x` divided by the divisor.
+        pub fn rem(&self, x: &BigUint) -> u64 {
+            self.rem_slice(&x.data)
+        }
+    );
+
+    cfg_digit!(
+        /// Replaces `x` with its quotient by the divisor, returning the remainder.
+        pub(super) fn div_rem_assign(&self, x: &mut BigUint) -> u64 {
+            let mut r = 0;
+            for c in x.data.chunks_mut(2).rev() {
+                let u = u64::from(c[0]) | u64::from(c.get(1).copied().unwrap_or(0)) << 32;
+                let (q, rem) = self.div_wide(r, u);
+                c[0] = q as u32;
+                if let Some(hi) = c.get_mut(1) {
+                    *hi = (q >> 32) as u32;
+                }
+                r = rem;
+            }
+            x.normalize();
+            r
+        }
+
+        /// Replaces `x` with its quotient by the divisor, returning the remainder.
+        pub(super) fn div_rem_assign(&self, x: &mut BigUint) -> u64 {
+            let r = self.div_rem_slice(&mut x.data);
+            x.normalize();
+            r
+        }
+    );
+}
+
+#[test]
+fn test_digit_divisor() {
+    use super::division::div_rem_digit;
+    use crate::big_digit::BigDigit;
+
+    let words = [0xf1e2_d3c4_b5a6_9788u64; 9];
+    let n = BigUint::new(
+        words
+            .iter()
+            .flat_map(|&w| [w as u32, (w >> 32) as u32])
+            .collect(),
+    );
+    for &d in &[
+        1,
+        2,
+        3,
+        10,
+        1 << 31,
+        u32::MAX as u64,
+        1 << 32,
+        0x1234_5678_9abc,
+        1 << 63,
+        u64::MAX,
+    ] {
+        let divisor = DigitDivisor::new(d);
+        let expected = if let Ok(d) = u32::try_from(d) {
+            let (q, r) = div_rem_digit(n.clone(), d as BigDigit);
+            #[allow(clippy::useless_conversion)]
+            let r = u64::from(r);
+            (q, r)
+        } else {
+            (&n / d, (&n % d).try_into().unwrap())
+        };
+        assert_eq!(divisor.div_rem(&n), expected, "{}", d);
+        assert_eq!(divisor.rem(&n), expected.1, "{}", d);
+
+        assert_eq!(divisor.rem_slice(&words), expected.1);
+        let mut q = words;
+        assert_eq!(divisor.div_rem_slice(&mut q), expected.1);
+        let q = q.iter().flat_map(|&w| [w as u32, (w >> 32) as u32]);
+        assert_eq!(BigUint::new(q.collect()), expected.0);
+    }
+}
@@ -0,0 +1,356 @@
+use core::iter::FusedIterator;
+
+use crate::big_digit::{self, BigDigit};
+
+const BITS: u64 = big_digit::BITS as u64;
+
+/// An iterator of `u32` digits representation of a `BigUint` or `BigInt`,
+/// ordered least significant digit first.
+#[derive(Clone, Debug)]
+pub struct U32Digits<'a> {
+    data: &'a [BigDigit],
+    front: usize,
+    back: usize,
+}
+
+cfg_digit!(
+    impl<'a> U32Digits<'a> {
+        #[inline]
+        pub(super) fn new(data: &'a [BigDigit]) -> Self {
+            U32Digits {
+                data,
+                front: 0,
+                back: data.len(),
+            }
+        }
+
+        #[inline]
+        fn get(&self, i: usize) -> u32 {
+            self.data[i]
+        }
+    }
+
+    impl<'a> U32Digits<'a> {
+        #[inline]
+        pub(super) fn new(data: &'a [BigDigit]) -> Self {
+            // the high half of the top digit may be zero, and is then left out
+            let last_hi_is_zero = data.last().is_some_and(|&last| last >> 32 == 0);
+            U32Digits {
+                data,
+                front: 0,
+                back: data.len() * 2 - usize::from(last_hi_is_zero),
+            }
+        }
+
+        #[inline]
+        fn get(&self, i: usize) -> u32 {
+            (self.data[i / 2] >> (32 * (i % 2))) as u32
+        }
+    }
+);
+
+/// An iterator of `u64` digits representation of a `BigUint` or `BigInt`,
+/// ordered least significant digit first.
+#[derive(Clone, Debug)]
+pub struct U64Digits<'a> {
+    data: &'a [BigDigit],
+    front: usize,
+    back: usize,
+}
+
+cfg_digit!(
+    impl<'a> U64Digits<'a> {
+        #[inline]
+        pub(super) fn new(data: &'a [BigDigit]) -> Self {
+            U64Digits {
+                data,
+                front: 0,
+                back: data.len().div_ceil(2),
+            }
+        }
+
+        #[inline]
+        fn get(&self, i: usize) -> u64 {
+            let lo = self.data[2 * i];
+            let hi = self.data.get(2 * i + 1).copied().unwrap_or(0);
+            big_digit::to_doublebigdigit(hi, lo)
+        }
+    }
+
+    impl<'a> U64Digits<'a> {
+        #[inline]
+        pub(super) fn new(data: &'a [BigDigit]) -> Self {
+            U64Digits {
+                data,
+                front: 0,
+                back: data.len(),
+            }
+        }
+
+        #[inline]
+        fn get(&self, i: usize) -> u64 {
+            self.data[i]
+        }
+    }
+);
+
+macro_rules! impl_digits_iter {
+    ($Digits:ident, $digit:ty) => {
+        impl Iterator for $Digits<'_> {
+            type Item = $digit;
+
+            #[inline]
+            fn next(&mut self) -> Option<$digit> {
+                if self.front == self.back {
+                    return None;
+                }
+                let digit = self.get(self.front);
+                self.front += 1;
+                Some(digit)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.len();
+                (len, Some(len))
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<$digit> {
+                self.front += n.min(self.len());
+                self.next()
+            }
+
+            #[inline]
+            fn last(mut self) -> Option<$digit> {
+                self.next_back()
+            }
+
+            #[inline]
+            fn count(self) -> usize {
+                self.len()
+            }
+        }
+
+        impl DoubleEndedIterator for $Digits<'_> {
+            #[inline]
+            fn next_back(&mut self) -> Option<$digit> {
+                if self.front == self.back {
+                    return None;
+                }
+                self.back -= 1;
+                Some(self.get(self.back))
+            }
+        }
+
+        impl ExactSizeIterator for $Digits<'_> {
+            #[inline]
+            fn len(&self) -> usize {
+                self.back - self.front
+            }
+        }
+
+        impl FusedIterator for $Digits<'_> {}
+    };
+}
+
+impl_digits_iter!(U32Digits, u32);
+impl_digits_iter!(U64Digits, u64);
+
+/// An iterator over the bits of a [`BigUint`](crate::BigUint), from least significant to the
+/// most significant one bit.
+///
+/// This is created by [`BigUint::iter_bits`](crate::BigUint::iter_bits). It can also be run from
+/// the top, as left-to-right exponentiation wants.
+#[derive(Clone, Debug)]
+pub struct IterBits<'a> {
+    data: &'a [BigDigit],
+    front: u64,
+    back: u64,
+}
+
+impl<'a> IterBits<'a> {
+    #[inline]
+    pub(super) fn new(data: &'a [BigDigit], bits: u64) -> Self {
+        IterBits {
+            data,
+            front: 0,
+            back: bits,
+        }
+    }
+
+    #[inline]
+    fn get(&self, bit: u64) -> bool {
+        (self.data[(bit / BITS) as usize] >> (bit % BITS)) & 1 != 0
+    }
+}
+
+impl Iterator for IterBits<'_> {
+    type Item = bool;
+
+    #[inline]
+    fn next(&mut self) -> Option<bool> {
+        if self.front == self.back {
+            return None;
+        }
+        let bit = self.get(self.front);
+        self.front += 1;
+        Some(bit)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<bool> {
+        self.front = (self.front + n as u64).min(self.back);
+        self.next()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+}
+
+impl DoubleEndedIterator for IterBits<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<bool> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.get(self.back))
+    }
+}
+
+impl ExactSizeIterator for IterBits<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        (self.back - self.front) as usize
+    }
+}
+
+impl FusedIterator for IterBits<'_> {}
+
+/// An iterator over the positions of the one bits of a [`BigUint`](crate::BigUint), in
+/// increasing order.
+///
+/// This is created by [`BigUint::iter_ones`](crate::BigUint::iter_ones). It skips zero digits
+/// whole and finds each one bit in a digit with `trailing_zeros`.
+#[derive(Clone, Debug)]
+pub struct IterOnes<'a> {
+    data: &'a [BigDigit],
+    index: usize,
+    current: BigDigit,
+}
+
+impl<'a> IterOnes<'a> {
+    #[inline]
+    pub(super) fn new(data: &'a [BigDigit]) -> Self {
+        IterOnes {
+            data,
+            index: 0,
+            current: data.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl Iterator for IterOnes<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.data.get(self.index)?;
+        }
+        let zeros = u64::from(self.current.trailing_zeros());
+        // clear the lowest one bit
+        self.current &= self.current - 1;
+        Some(self.index as u64 * BITS + zeros)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.data.len().saturating_sub(self.index + 1);
+        let current = self.current.count_ones() as usize;
+        (current, Some(current + rest * BITS as usize))
+    }
+}
+
+impl FusedIterator for IterOnes<'_> {}
+
+#[test]
+fn test_iter_bits() {
+    use crate::BigUint;
+    use alloc::vec::Vec;
+
+    let x = BigUint::new(alloc::vec![0x8000_0001, 0, 0x0000_0500]);
+    let ones: Vec<u64> = x.iter_ones().collect();
+    assert_eq!(ones, [0, 31, 72, 74]);
+    assert_eq!(BigUint::ZERO.iter_ones().next(), None);
+
+    let bits: Vec<bool> = x.iter_bits().collect();
+    assert_eq!(bits.len() as u64, x.bits());
+    assert_eq!(x.iter_bits().len(), bits.len());
+    for (i, &bit) in bits.iter().enumerate() {
+        assert_eq!(bit, x.bit(i as u64));
+        assert_eq!(bit, ones.contains(&(i as u64)));
+    }
+    let rev: Vec<bool> = x.iter_bits().rev().collect();
+    assert!(rev.iter().eq(bits.iter().rev()));
+    assert_eq!(x.iter_bits().nth(72), Some(true));
+    assert_eq!(x.iter_bits().nth(75), None);
+
+    let mut iter = x.iter_bits();
+    assert_eq!(iter.next_back(), Some(true));
+    assert_eq!(iter.next(), Some(true));
+    assert_eq!(iter.len(), bits.len() - 2);
+    assert_eq!(BigUint::ZERO.iter_bits().next(), None);
+}
+
+#[test]
+fn test_iter_digits() {
+    use crate::BigUint;
+    use alloc::vec::Vec;
+
+    let cases: [&[u32]; 6] = [
+        &[],
+        &[1],
+        &[1, 2],
+        &[1, 2, 3],
+        &[0, 0, 0, 0x8000_0000],
+        &[0xffff_ffff, 0, 7],
+    ];
+    for &digits in &cases {
+        let x = BigUint::from_slice(digits);
+        let u32s: Vec<u32> = x.iter_u32_digits().collect();
+        assert_eq!(u32s, digits);
+        assert_eq!(x.iter_u32_digits().len(), digits.len());
+        assert!(x.iter_u32_digits().rev().eq(digits.iter().rev().copied()));
+
+        let u64s: Vec<u64> = digits
+            .chunks(2)
+            .map(|c| u64::from(c[0]) | (u64::from(c.get(1).copied().unwrap_or(0)) << 32))
+            .collect();
+        assert_eq!(x.to_u64_digits(), u64s);
+        assert_eq!(x.iter_u64_digits().len(), u64s.len());
+        assert!(x.iter_u64_digits().rev().eq(u64s.iter().rev().copied()));
+        assert_eq!(BigUint::from_u64_digits(u64s.clone()), x);
+
+        let mut iter = x.iter_u32_digits();
+        assert_eq!(iter.nth(1), digits.get(1).copied());
+        assert_eq!(iter.len(), digits.len().saturating_sub(2));
+        assert_eq!(x.iter_u32_digits().last(), digits.last().copied());
+        assert_eq!(x.iter_u64_digits().last(), u64s.last().copied());
+    }
+
+    let mut x = BigUint::from(5u32);
+    x.assign_from_u64_slice(&[u64::MAX, 0, 0]);
+    assert_eq!(x, BigUint::from(u64::MAX));
+    assert_eq!(BigUint::from_u64_digits(alloc::vec![0, 0]), BigUint::ZERO);
+}
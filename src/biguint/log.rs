@@ -0,0 +1,202 @@
+use super::BigUint;
+
+use alloc::boxed::Box;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use num_traits::{One, ToPrimitive};
+
+/// `log10(2)` as a 64-bit fraction, rounded down.
+const LOG10_2: u128 = 0x4d10_4d42_7de7_fbcc;
+
+/// How many of the squares `5^(2^k)` are cached, for k below this. The largest is `5^(2^17)`,
+/// about 38 KB, and all of them together about twice that.
+const POW5_CACHED: usize = 18;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicPtr<BigUint> = AtomicPtr::new(ptr::null_mut());
+
+/// The squares `5^(2^k)` for k below `POW5_CACHED`, each computed the first time it is needed.
+static POW5_SQUARES: [AtomicPtr<BigUint>; POW5_CACHED] = [EMPTY; POW5_CACHED];
+
+/// Returns the cached square `5^(2^k)`, for k below `POW5_CACHED`.
+///
+/// A square is installed once and then never freed. If another thread installs one first, ours
+/// is dropped, so the cache holds at most one of each.
+fn pow5_square(k: usize) -> &'static BigUint {
+    let slot = &POW5_SQUARES[k];
+    let current = slot.load(Ordering::Acquire);
+    if !current.is_null() {
+        // SAFETY: non-null pointers only come from the `Box::into_raw` below, and are never freed.
+        return unsafe { &*current };
+    }
+
+    let square = match k {
+        0 => BigUint::from(5u32),
+        _ => pow5_square(k - 1).square(),
+    };
+    let square = Box::into_raw(Box::new(square));
+    match slot.compare_exchange(ptr::null_mut(), square, Ordering::AcqRel, Ordering::Acquire) {
+        // SAFETY: `square` was just leaked above, and is now owned by the cache.
+        Ok(_) => unsafe { &*square },
+        Err(installed) => {
+            // SAFETY: `square` was leaked above and never shared, and `installed` came from
+            // another call of this function.
+            unsafe {
+                drop(Box::from_raw(square));
+                &*installed
+            }
+        }
+    }
+}
+
+/// Returns `5^n`, as a product of squares `5^(2^k)`, the smaller of which are cached.
+pub(super) fn pow5(n: u64) -> BigUint {
+    if n < 28 {
+        return BigUint::from(5u64.pow(n as u32));
+    }
+    let len = (u64::BITS - n.leading_zeros()) as usize;
+    let mut power = BigUint::one();
+    // the latest of the squares beyond the cache, if any
+    let mut uncached: Option<BigUint> = None;
+    for k in 0..len {
+        let square = if k < POW5_CACHED {
+            pow5_square(k)
+        } else {
+            let next = match uncached.take() {
+                Some(last) => last.square(),
+                None => pow5_square(POW5_CACHED - 1).square(),
+            };
+            &*uncached.insert(next)
+        };
+        if (n >> k) & 1 == 1 {
+            power *= square;
+        }
+    }
+    power
+}
+
+/// Returns `floor(log10(x))`, for `x > 0`.
+///
+/// With 2^(b-1) <= x < 2^b, the log is at least floor((b - 1) log10(2)), and less than that plus
+/// 1.302. So this starts from that estimate, made with a fraction rounded down so that it is
+/// never too large, and steps up while x is at least the next power of ten. Comparing `x >> e`
+/// with `5^e` is the same as comparing x with 10^e, without a shift of the power.
+pub(super) fn ilog10(x: &BigUint) -> u64 {
+    debug_assert!(x.bits() > 0);
+    if let Some(x) = x.to_u64() {
+        return x.ilog10().into();
+    }
+    let mut e = ((u128::from(x.bits() - 1) * LOG10_2) >> 64) as u64;
+    while (x >> (e + 1)) >= pow5(e + 1) {
+        e += 1;
+    }
+    e
+}
+
+/// Returns `floor(log(x))` in `base`, for `x > 0` and `base >= 2`.
+///
+/// For other than a power of two or ten, this finds the squares base^(2^k) up to x, and then the
+/// exponent one bit at a time from the top, as the largest product of them not above x.
+pub(super) fn ilog(x: &BigUint, base: &BigUint) -> u64 {
+    debug_assert!(x.bits() > 0 && base.bits() > 1);
+    if let (Some(x), Some(base)) = (x.to_u64(), base.to_u64()) {
+        return x.ilog(base).into();
+    }
+    if base.is_power_of_two() {
+        // With base = 2^m, the log is floor(floor(log2(x)) / m).
+        return (x.bits() - 1) / (base.bits() - 1);
+    }
+    if *base == BigUint::from(10u32) {
+        return ilog10(x);
+    }
+    if x < base {
+        return 0;
+    }
+
+    let mut squares = alloc::vec![base.clone()];
+    loop {
+        let last = squares.last().unwrap();
+        // The square has at least 2 * bits - 1 bits, so skip it when that is already too many.
+        if 2 * last.bits() - 1 > x.bits() {
+            break;
+        }
+        let next = last.square();
+        if next > *x {
+            break;
+        }
+        squares.push(next);
+    }
+
+    let mut e = 0;
+    let mut power = BigUint::one();
+    for (k, square) in squares.iter().enumerate().rev() {
+        let next = &power * square;
+        if next <= *x {
+            power = next;
+            e += 1 << k;
+        }
+    }
+    e
+}
+
+#[test]
+fn test_ilog() {
+    use num_traits::Pow;
+
+    for n in [0u64, 1, 27, 28, 29, 64, 100, 1000, 1025] {
+        assert_eq!(pow5(n), Pow::pow(BigUint::from(5u32), n));
+    }
+    // past the cached squares
+    let n = (1 << POW5_CACHED) + 3;
+    assert_eq!(pow5(n), Pow::pow(BigUint::from(5u32), n));
+
+    for e in [19u64, 20, 21, 63, 64, 100, 1000, 1234] {
+        let p = Pow::pow(BigUint::from(10u32), e);
+        assert_eq!(ilog10(&p), e);
+        assert_eq!(ilog10(&(&p - 1u32)), e - 1);
+        assert_eq!(ilog10(&(&p + 1u32)), e);
+        assert_eq!(ilog10(&(&p * 9u32)), e);
+    }
+    // around powers of two, where the estimate is tightest
+    for b in [64u64, 65, 100, 332, 333, 3322, 3323] {
+        let x = BigUint::one() << b;
+        for x in [&x - 1u32, x.clone()] {
+            let e = ilog10(&x);
+            assert!(Pow::pow(BigUint::from(10u32), e) <= x);
+            assert!(Pow::pow(BigUint::from(10u32), e + 1) > x);
+        }
+    }
+
+    for base in [3u32, 7, 10, 16, 255, 1000, u32::MAX] {
+        let base = BigUint::from(base);
+        for e in [1u64, 2, 3, 20, 50, 101] {
+            let p = Pow::pow(&base, e);
+            assert_eq!(ilog(&p, &base), e);
+            assert_eq!(ilog(&(&p - 1u32), &base), e - 1);
+            assert_eq!(ilog(&(&p + 1u32), &base), e);
+        }
+    }
+    let base = BigUint::from(3u32) << 70u8;
+    let x = Pow::pow(&base, 5u32) * 2u32;
+    assert_eq!(ilog(&x, &base), 5);
+    assert_eq!(ilog(&base, &x), 0);
+
+    for (x, p) in [
+        (0u32, 1u32),
+        (1, 1),
+        (2, 2),
+        (3, 4),
+        (5, 8),
+        (8, 8),
+        (9, 16),
+    ] {
+        assert_eq!(BigUint::from(x).next_power_of_two(), BigUint::from(p));
+        assert_eq!(BigUint::from(x).is_power_of_two(), x == p);
+    }
+    let p = BigUint::one() << 100u8;
+    assert!(p.is_power_of_two());
+    assert!(!(&p + 1u32).is_power_of_two());
+    assert_eq!((&p - 1u32).next_power_of_two(), p);
+    assert_eq!((&p + 1u32).next_power_of_two(), &p << 1u8);
+}
@@ -3,14 +3,21 @@ use core::mem;
 use core::ops::Shl;
 use num_traits::One;
 
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
-
 use crate::big_digit::{self, BigDigit, DoubleBigDigit};
 use crate::biguint::BigUint;
+use crate::executor;
+use crate::tuning;
 
-struct MontyReducer {
+/// Montgomery arithmetic modulo an odd `m`, with everything that only depends on `m` computed
+/// once, so that several exponentiations with the same modulus can share it.
+pub(super) struct MontyContext<'a> {
+    m: &'a BigUint,
     n0inv: BigDigit,
+    num_words: usize,
+    /// 2^(2*W*len(m)) mod m, with equal length to that of m
+    rr: BigUint,
+    /// 1, with equal length to that of m
+    one: BigUint,
 }
 
 fn inv_mod_alt(b: BigDigit) -> BigDigit {
@@ -28,10 +35,32 @@ fn inv_mod_alt(b: BigDigit) -> BigDigit {
     k0.wrapping_neg()
 }
 
-impl MontyReducer {
-    fn new(n: &BigUint) -> Self {
-        let n0inv = inv_mod_alt(n.data[0]);
-        MontyReducer { n0inv }
+impl<'a> MontyContext<'a> {
+    pub(super) fn new(m: &'a BigUint) -> Self {
+        assert!(m.data[0] & 1 == 1);
+        let n0inv = inv_mod_alt(m.data[0]);
+        let num_words = m.data.len();
+
+        let mut rr = BigUint::one();
+        rr = (rr.shl(2 * num_words as u64 * u64::from(big_digit::BITS))) % m;
+        if rr.data.len() < num_words {
+            rr.data.resize(num_words, 0);
+        }
+        let mut one = BigUint::one();
+        one.data.resize(num_words, 0);
+
+        MontyContext {
+            m,
+            n0inv,
+            num_words,
+            rr,
+            one,
+        }
+    }
+
+    #[inline]
+    fn mul(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        montgomery(x, y, self.m, self.n0inv, self.num_words)
     }
 }
 
@@ -43,7 +72,6 @@ impl MontyReducer {
 /// x and y are required to satisfy 0 <= z < 2**(n*_W) and then the result
 /// z is guaranteed to satisfy 0 <= z < 2**(n*_W), but it may not be < m.
 /// montgomery 함수(기존 그대로)
-#[allow(clippy::many_single_char_names)]
 fn montgomery(x: &BigUint, y: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> BigUint {
     assert!(
         x.data.len() == n && y.data.len() == n && m.data.len() == n,
@@ -54,18 +82,57 @@ fn montgomery(x: &BigUint, y: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> B
         n
     );
 
-    let mut z = BigUint::ZERO;
-    z.data.resize(n * 2, 0);
+    BigUint {
+        data: montgomery_digits(&x.data, &y.data, &m.data, k),
+    }
+}
+
+/// The digits of [`montgomery`], for operands as long as `m`. The product has as many digits as
+/// `m`, including any high zero digits.
+pub(crate) fn montgomery_digits(
+    x: &[BigDigit],
+    y: &[BigDigit],
+    m: &[BigDigit],
+    k: BigDigit,
+) -> Vec<BigDigit> {
+    if executor::split_count(m.len()) > 1 {
+        let z = montgomery_with(x, y, m, k, add_mul_vvw);
+        executor::verify(
+            "Montgomery product",
+            &z,
+            || montgomery_with(x, y, m, k, add_mul_vvw_serial),
+            (x, y, m),
+        );
+        z
+    } else {
+        montgomery_with(x, y, m, k, add_mul_vvw_serial)
+    }
+}
+
+type AddMulFn = fn(&mut [BigDigit], &[BigDigit], BigDigit) -> BigDigit;
+
+#[allow(clippy::many_single_char_names)]
+fn montgomery_with(
+    x: &[BigDigit],
+    y: &[BigDigit],
+    m: &[BigDigit],
+    k: BigDigit,
+    add_mul_vvw: AddMulFn,
+) -> Vec<BigDigit> {
+    let n = m.len();
+    debug_assert!(x.len() == n && y.len() == n);
+
+    let mut z = vec![0; n * 2];
 
     let mut c: BigDigit = 0;
     for i in 0..n {
         // 여기서 add_mul_vvw가 호출됨. (병렬화 대상)
-        let c2 = add_mul_vvw(&mut z.data[i..n + i], &x.data, y.data[i]);
-        let t = z.data[i].wrapping_mul(k);
-        let c3 = add_mul_vvw(&mut z.data[i..n + i], &m.data, t);
+        let c2 = add_mul_vvw(&mut z[i..n + i], x, y[i]);
+        let t = z[i].wrapping_mul(k);
+        let c3 = add_mul_vvw(&mut z[i..n + i], m, t);
         let cx = c.wrapping_add(c2);
         let cy = cx.wrapping_add(c3);
-        z.data[n + i] = cy;
+        z[n + i] = cy;
         if cx < c2 || cy < c3 {
             c = 1;
         } else {
@@ -74,13 +141,13 @@ fn montgomery(x: &BigUint, y: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> B
     }
 
     if c == 0 {
-        z.data = z.data[n..].to_vec();
+        z = z[n..].to_vec();
     } else {
         {
-            let (first, second) = z.data.split_at_mut(n);
-            sub_vv(first, second, &m.data);
+            let (first, second) = z.split_at_mut(n);
+            sub_vv(first, second, m);
         }
-        z.data = z.data[..n].to_vec();
+        z.truncate(n);
     }
 
     z
@@ -88,18 +155,16 @@ fn montgomery(x: &BigUint, y: &BigUint, m: &BigUint, k: BigDigit, n: usize) -> B
 
 #[inline(always)]
 fn add_mul_vvw(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDigit {
-    #[cfg(feature = "parallel")]
-    {
-        add_mul_vvw_parallel(z, x, y)
-    }
-    #[cfg(not(feature = "parallel"))]
-    {
+    let chunks = executor::split_count(x.len());
+    if chunks > 1 {
+        add_mul_vvw_parallel(z, x, y, chunks)
+    } else {
         add_mul_vvw_serial(z, x, y)
     }
 }
 
 #[inline(always)]
-fn add_mul_vvw_serial(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDigit {
+pub(crate) fn add_mul_vvw_serial(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDigit {
     let mut c = 0;
     for (zi, xi) in z.iter_mut().zip(x.iter()) {
         let (z1, z0) = mul_add_www(*xi, y, *zi);
@@ -110,50 +175,47 @@ fn add_mul_vvw_serial(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDig
     c
 }
 
-#[cfg(feature = "parallel")]
-fn add_mul_vvw_parallel(z: &mut [BigDigit], x: &[BigDigit], y: BigDigit) -> BigDigit {
-    use core::sync::atomic::{AtomicU64, Ordering};
-
+/// Splits x into `chunks` pieces multiplied independently on the executor, then ripples the
+/// carry out of each piece into the next one.
+fn add_mul_vvw_parallel(
+    z: &mut [BigDigit],
+    x: &[BigDigit],
+    y: BigDigit,
+    chunks: usize,
+) -> BigDigit {
     let n = x.len();
     assert!(z.len() >= n);
 
-    // 1) 먼저 (x_i * y) + z_i를 병렬로 계산하여 128비트 값(partial_sum)을 구함
-    //    partial_sum[i] = (carry_hi << BITS) + lo
-    //    여기서 carry_hi 는 mul_add_www에서 나오는 상위 BigDigit
-    //    lo 는 최종 z[i]에 들어갈 값 (add_ww 과정 일부 포함)
-    let partials: Vec<u128> = z
-        .par_iter()
-        .zip(x.par_iter())
-        .map(|(&zi, &xi)| {
-            // 기존 mul_add_www + add_ww 과정을 한꺼번에 수행
-            let (mul_hi, mul_lo) = mul_add_www(xi, y, zi);
-            // 이 시점에서 mul_lo + carry_c (0)은 아래 add_ww와 동일하므로
-            // z1, z0 = add_ww(mul_lo, 0, 0) 형태
-            // 결국 z1은 mul_lo 오버플로 체크 정도지만, 여기서는 0이니 단순화
-            ((mul_hi as u128) << big_digit::BITS) | (mul_lo as u128)
-        })
+    let size = n.div_ceil(chunks);
+    let mut pieces: Vec<_> = z[..n]
+        .chunks_mut(size)
+        .zip(x.chunks(size))
+        .map(|(zi, xi)| (zi, xi, 0))
         .collect();
+    executor::for_each(&mut pieces, |(zi, xi, c)| {
+        *c = add_mul_vvw_serial(zi, xi, y)
+    });
 
-    // 2) 위 단계에서 carry를 제대로 전파하지 않았으므로,
-    //    이제 직렬로 partials를 순회하며 carry를 전파한다.
-    //    partials[i]는 최대 2^BITS + 2^BITS = 2^(BITS+1) 정도이므로
-    //    u128에 충분히 들어갑니다.(BITS가 64라면 128비트 필요)
-    let mut c = 0u128;
-    for i in 0..n {
-        let sum = partials[i] + c;
-        let z_new = sum as BigDigit; // 하위 BITS
-        let carry_out = sum >> big_digit::BITS; // 상위 BITS
-        z[i] = z_new;
-        c = carry_out;
+    let mut c = 0;
+    for (zi, _, ci) in pieces {
+        // A piece plus its incoming carry still fits in one more digit, so `c + ci` can't
+        // overflow.
+        for d in zi.iter_mut() {
+            if c == 0 {
+                break;
+            }
+            let (c_, d_) = add_ww(*d, c, 0);
+            *d = d_;
+            c = c_;
+        }
+        c += ci;
     }
-
-    // 마지막 carry 반환 (0 or 1 정도일 것)
-    c as BigDigit
+    c
 }
 
 /// sub_vv (직렬)
 #[inline(always)]
-fn sub_vv(z: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) -> BigDigit {
+pub(crate) fn sub_vv(z: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) -> BigDigit {
     let mut c = 0;
     for (i, (xi, yi)) in x.iter().zip(y.iter()).enumerate().take(z.len()) {
         let zi = xi.wrapping_sub(*yi).wrapping_sub(c);
@@ -179,82 +241,71 @@ fn mul_add_www(x: BigDigit, y: BigDigit, c: BigDigit) -> (BigDigit, BigDigit) {
     ((z >> big_digit::BITS) as BigDigit, z as BigDigit)
 }
 
-/// Calculates x ** y mod m using a fixed, 4-bit window.
-#[allow(clippy::many_single_char_names)]
+/// Calculates x ** y mod m using a fixed window, 4 bits wide by default.
 pub(super) fn monty_modpow(x: &BigUint, y: &BigUint, m: &BigUint) -> BigUint {
-    assert!(m.data[0] & 1 == 1);
-    let mr = MontyReducer::new(m);
-    let num_words = m.data.len();
+    MontyContext::new(m).modpow(x, y)
+}
 
-    let mut x = x.clone();
-    if x.data.len() > num_words {
-        x %= m;
-    }
-    if x.data.len() < num_words {
-        x.data.resize(num_words, 0);
-    }
+impl MontyContext<'_> {
+    /// Calculates x ** y mod m using a fixed window, 4 bits wide by default.
+    pub(super) fn modpow(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        let m = self.m;
+        let num_words = self.num_words;
 
-    // rr = 2^(2*W*len(m)) mod m
-    let mut rr = BigUint::one();
-    rr = (rr.shl(2 * num_words as u64 * u64::from(big_digit::BITS))) % m;
-    if rr.data.len() < num_words {
-        rr.data.resize(num_words, 0);
-    }
-    // one = 1, with equal length to that of m
-    let mut one = BigUint::one();
-    one.data.resize(num_words, 0);
-
-    let n = 4;
-    // powers[i] = x^i
-    let mut powers = Vec::with_capacity(1 << n);
-    powers.push(montgomery(&one, &rr, m, mr.n0inv, num_words));
-    powers.push(montgomery(&x, &rr, m, mr.n0inv, num_words));
-    for i in 2..1 << n {
-        let r = montgomery(&powers[i - 1], &powers[1], m, mr.n0inv, num_words);
-        powers.push(r);
-    }
+        let mut x = x.clone();
+        if x.data.len() > num_words {
+            x %= m;
+        }
+        if x.data.len() < num_words {
+            x.data.resize(num_words, 0);
+        }
 
-    // initialize z = 1 (Montgomery 1)
-    let mut z = powers[0].clone();
-    z.data.resize(num_words, 0);
-    let mut zz = BigUint::ZERO;
-    zz.data.resize(num_words, 0);
-
-    // windowed exponentiation
-    for i in (0..y.data.len()).rev() {
-        let mut yi = y.data[i];
-        let mut j = 0;
-        while j < big_digit::BITS {
-            if i != y.data.len() - 1 || j != 0 {
-                zz = montgomery(&z, &z, m, mr.n0inv, num_words);
-                z = montgomery(&zz, &zz, m, mr.n0inv, num_words);
-                zz = montgomery(&z, &z, m, mr.n0inv, num_words);
-                z = montgomery(&zz, &zz, m, mr.n0inv, num_words);
+        let n = tuning::monty_window_bits() as u8;
+        // powers[i] = x^i
+        let mut powers = Vec::with_capacity(1 << n);
+        powers.push(self.mul(&self.one, &self.rr));
+        powers.push(self.mul(&x, &self.rr));
+        for i in 2..1 << n {
+            let r = self.mul(&powers[i - 1], &powers[1]);
+            powers.push(r);
+        }
+
+        // initialize z = 1 (Montgomery 1)
+        let mut z = powers[0].clone();
+        z.data.resize(num_words, 0);
+        let mut zz = BigUint::ZERO;
+        zz.data.resize(num_words, 0);
+
+        // windowed exponentiation
+        for i in (0..y.data.len()).rev() {
+            let mut yi = y.data[i];
+            let mut j = 0;
+            while j < big_digit::BITS {
+                if i != y.data.len() - 1 || j != 0 {
+                    for _ in 0..n {
+                        zz = self.mul(&z, &z);
+                        mem::swap(&mut z, &mut zz);
+                    }
+                }
+                zz = self.mul(&z, &powers[(yi >> (big_digit::BITS - n)) as usize]);
+                mem::swap(&mut z, &mut zz);
+                yi <<= n;
+                j += n;
             }
-            zz = montgomery(
-                &z,
-                &powers[(yi >> (big_digit::BITS - n)) as usize],
-                m,
-                mr.n0inv,
-                num_words,
-            );
-            mem::swap(&mut z, &mut zz);
-            yi <<= n;
-            j += n;
         }
-    }
 
-    // convert to regular number
-    zz = montgomery(&z, &one, m, mr.n0inv, num_words);
-    zz.normalize();
+        // convert to regular number
+        zz = self.mul(&z, &self.one);
+        zz.normalize();
 
-    // 마지막으로 한 번 더 m보다 큰지 확인하고 빼주기 (Go 이슈 #13907 대응)
-    if zz >= *m {
-        zz -= m;
+        // 마지막으로 한 번 더 m보다 큰지 확인하고 빼주기 (Go 이슈 #13907 대응)
         if zz >= *m {
-            zz %= m;
+            zz -= m;
+            if zz >= *m {
+                zz %= m;
+            }
         }
+        zz.normalize();
+        zz
     }
-    zz.normalize();
-    zz
 }
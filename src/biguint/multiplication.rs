@@ -1,15 +1,18 @@
 use super::addition::{__add2, add2};
-use super::subtraction::sub2;
+use super::ntt::{mac_ntt, sqr_ntt};
+use super::subtraction::{__sub2, sub2};
 use super::{biguint_from_vec, cmp_slice, BigUint, IntDigits};
 
 use crate::big_digit::{self, BigDigit, DoubleBigDigit};
 use crate::Sign::{self, Minus, NoSign, Plus};
+use crate::{executor, tuning};
 use crate::{BigInt, UsizePromotion};
 
+use alloc::vec::Vec;
 use core::cmp::Ordering;
 use core::iter::Product;
 use core::ops::{Mul, MulAssign};
-use num_traits::{CheckedMul, FromPrimitive, One, Zero};
+use num_traits::{CheckedMul, FromPrimitive, MulAdd, MulAddAssign, One, Zero};
 
 #[inline]
 pub(super) fn mac_with_carry(
@@ -57,6 +60,32 @@ fn mac_digit(acc: &mut [BigDigit], b: &[BigDigit], c: BigDigit) {
     assert_eq!(final_carry, 0, "carry overflow during multiplication!");
 }
 
+/// Three argument multiply subtract, modulo the length of acc:
+/// acc -= b * c
+///
+/// Returns whether the product was larger, so that acc wrapped around.
+fn msb_digit(acc: &mut [BigDigit], b: &[BigDigit], c: BigDigit) -> bool {
+    if c == 0 {
+        return false;
+    }
+
+    // The borrow is at most one more than the high digit of a digit product, so it fits.
+    let mut borrow: DoubleBigDigit = 0;
+    let (a_lo, a_hi) = acc.split_at_mut(b.len());
+
+    for (a, &b) in a_lo.iter_mut().zip(b) {
+        let (p_hi, p_lo) = big_digit::from_doublebigdigit(
+            DoubleBigDigit::from(b) * DoubleBigDigit::from(c) + borrow,
+        );
+        let (diff, overflow) = a.overflowing_sub(p_lo);
+        *a = diff;
+        borrow = DoubleBigDigit::from(p_hi) + DoubleBigDigit::from(overflow);
+    }
+
+    let (borrow_hi, borrow_lo) = big_digit::from_doublebigdigit(borrow);
+    __sub2(a_hi, &[borrow_lo, borrow_hi]) != 0
+}
+
 fn bigint_from_slice(slice: &[BigDigit]) -> BigInt {
     BigInt::from(biguint_from_vec(slice.to_vec()))
 }
@@ -86,24 +115,34 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
     let acc = acc;
     let (x, y) = if b.len() < c.len() { (b, c) } else { (c, b) };
 
-    // We use four algorithms for different input sizes.
+    // We use several algorithms for different input sizes.
     //
     // - For small inputs, long multiplication is fastest.
     // - If y is at least least twice as long as x, split using Half-Karatsuba.
+    //   For large inputs, only much more lopsided operands are split this way.
     // - Next we use Karatsuba multiplication (Toom-2), which we have optimized
     //   to avoid unnecessary allocations for intermediate values.
-    // - For the largest inputs we use Toom-3, which better optimizes the
-    //   number of operations, but uses more temporary allocations.
+    // - For large inputs of similar length we use Toom-3, which better
+    //   optimizes the number of operations, but uses more temporary
+    //   allocations, and Toom-4 above that.
+    // - For large inputs of moderately different lengths we use unbalanced
+    //   Toom-Cook, splitting y into more parts than x: Toom-4/3, Toom-3/2 and
+    //   Toom-4/2, for ratios of about 4:3, 3:2 and 2:1 respectively.
+    // - For the largest inputs we switch to number-theoretic transforms, which
+    //   are quasi-linear, see the `ntt` module.
     //
-    // The thresholds are somewhat arbitrary, chosen by evaluating the results
-    // of `cargo bench --bench bigint multiply`.
+    // The default thresholds were chosen by evaluating the results of
+    // `cargo bench --bench bigint multiply`, and can be tuned for the host with
+    // the `autotune` binary, see the `tuning` module.
 
-    if x.len() <= 32 {
+    if x.len() <= tuning::mul_karatsuba_threshold() {
         // Long multiplication:
         for (i, xi) in x.iter().enumerate() {
             mac_digit(&mut acc[i..], y, *xi);
         }
-    } else if x.len() * 2 <= y.len() {
+    } else if x.len() * 2 <= y.len()
+        && (x.len() <= tuning::mul_toom3_threshold() || x.len() * 5 <= y.len() * 2)
+    {
         // Karatsuba Multiplication for factors with significant length disparity.
         //
         // The Half-Karatsuba Multiplication Algorithm is a specialized case of
@@ -162,7 +201,7 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
         // (x * high2) * NBASE ^ m2 + z0
         mac3(acc, x, low2);
         mac3(&mut acc[m2..], x, high2);
-    } else if x.len() <= 256 {
+    } else if x.len() <= tuning::mul_toom3_threshold() {
         // Karatsuba multiplication:
         //
         // The idea is that we break x and y up into two smaller numbers that each have about half
@@ -275,6 +314,16 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
             }
             NoSign => (),
         }
+    } else if x.len() > tuning::mul_ntt_threshold() {
+        mac_ntt(acc, x, y);
+    } else if x.len() * 7 <= y.len() * 4 {
+        toom(acc, x, y, 2, 4);
+    } else if x.len() * 17 <= y.len() * 12 {
+        toom(acc, x, y, 2, 3);
+    } else if x.len() * 7 <= y.len() * 6 {
+        toom(acc, x, y, 3, 4);
+    } else if x.len() > tuning::mul_toom4_threshold() {
+        toom(acc, x, y, 4, 4);
     } else {
         // Toom-3 multiplication:
         //
@@ -376,35 +425,482 @@ fn mac3(mut acc: &mut [BigDigit], mut b: &[BigDigit], mut c: &[BigDigit]) {
         //
         // This particular sequence is given by Bodrato and is an interpolation
         // of the above equations.
-        let mut comp3: BigInt = (r3 - &r1) / 3u32;
-        let mut comp1: BigInt = (r1 - &r2) >> 1;
-        let mut comp2: BigInt = r2 - &r0;
-        comp3 = ((&comp2 - comp3) >> 1) + (&r4 << 1);
-        comp2 += &comp1 - &r4;
-        comp1 -= &comp3;
-
-        // Recomposition. The coefficients of the polynomial are now known.
-        //
-        // Evaluate at w(t) where t is our given base to get the result.
-        //
-        //     let bits = u64::from(big_digit::BITS) * i as u64;
-        //     let result = r0
-        //         + (comp1 << bits)
-        //         + (comp2 << (2 * bits))
-        //         + (comp3 << (3 * bits))
-        //         + (r4 << (4 * bits));
-        //     let result_pos = result.to_biguint().unwrap();
-        //     add2(&mut acc[..], &result_pos.data);
-        //
-        // But with less intermediate copying:
-        for (j, result) in [&r0, &comp1, &comp2, &comp3, &r4].iter().enumerate().rev() {
-            match result.sign() {
-                Plus => add2(&mut acc[i * j..], result.digits()),
-                Minus => sub2(&mut acc[i * j..], result.digits()),
-                NoSign => {}
+        toom3_interpolate(acc, i, r0, r1, r2, r3, r4);
+    }
+}
+
+/// Interpolates the Toom-3 product from w(0), w(1), w(-1), w(-2) and w(inf), see `mac3`,
+/// and adds it to `acc`, where `i` is the number of digits in each part.
+fn toom3_interpolate(
+    acc: &mut [BigDigit],
+    i: usize,
+    r0: BigInt,
+    r1: BigInt,
+    r2: BigInt,
+    r3: BigInt,
+    r4: BigInt,
+) {
+    let mut comp3: BigInt = (r3 - &r1) / 3u32;
+    let mut comp1: BigInt = (r1 - &r2) >> 1;
+    let mut comp2: BigInt = r2 - &r0;
+    comp3 = ((&comp2 - comp3) >> 1) + (&r4 << 1);
+    comp2 += &comp1 - &r4;
+    comp1 -= &comp3;
+
+    // Recomposition. The coefficients of the polynomial are now known.
+    //
+    // Evaluate at w(t) where t is our given base to get the result.
+    //
+    //     let bits = u64::from(big_digit::BITS) * i as u64;
+    //     let result = r0
+    //         + (comp1 << bits)
+    //         + (comp2 << (2 * bits))
+    //         + (comp3 << (3 * bits))
+    //         + (r4 << (4 * bits));
+    //     let result_pos = result.to_biguint().unwrap();
+    //     add2(&mut acc[..], &result_pos.data);
+    //
+    // But with less intermediate copying:
+    for (j, result) in [&r0, &comp1, &comp2, &comp3, &r4].iter().enumerate().rev() {
+        match result.sign() {
+            Plus => add2(&mut acc[i * j..], result.digits()),
+            Minus => sub2(&mut acc[i * j..], result.digits()),
+            NoSign => {}
+        }
+    }
+}
+
+/// Toom-Cook multiplication, acc += x * y, splitting x into `kx` parts and y into `ky` parts.
+///
+/// Like Toom-3 in `mac3`, x and y are treated as polynomials x(t) and y(t), and their product
+/// w(t) of degree d = kx + ky - 2 is found by evaluating it at d + 1 points: infinity, where
+/// w(inf) is the product of the leading parts, and 0, 1, -1, 2, -2, ...
+///
+/// Subtracting w(inf) * t^d from the other values leaves a polynomial of degree d - 1, which is
+/// interpolated in Newton form by divided differences. Since the nodes are integers and the
+/// polynomial has integer coefficients, every divided difference is an integer, so all the
+/// divisions are exact. Expanding the Newton form gives the coefficients of w(t), none of which
+/// can be negative, so they are simply added into acc.
+fn toom(acc: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit], kx: usize, ky: usize) {
+    let i = Ord::max(x.len().div_ceil(kx), y.len().div_ceil(ky));
+    let parts = |v: &[BigDigit], k: usize| -> Vec<BigInt> {
+        (0..k)
+            .map(|j| {
+                let lo = Ord::min(j * i, v.len());
+                let hi = Ord::min(lo + i, v.len());
+                bigint_from_slice(&v[lo..hi])
+            })
+            .collect()
+    };
+    let (xs, ys) = (parts(x, kx), parts(y, ky));
+    let d = kx + ky - 2;
+
+    let points: Vec<i64> = (0..d as i64)
+        .map(|j| if j % 2 == 1 { (j + 1) / 2 } else { -j / 2 })
+        .collect();
+    let eval = |parts: &[BigInt], t: i64| -> BigInt {
+        parts
+            .iter()
+            .rev()
+            .fold(BigInt::ZERO, |acc, part| acc * t + part)
+    };
+
+    // The pointwise products are independent, so they can run concurrently.
+    let mut products: Vec<(BigInt, BigInt, BigInt)> = points
+        .iter()
+        .map(|&t| (eval(&xs, t), eval(&ys, t), BigInt::ZERO))
+        .collect();
+    let w_inf = &xs[kx - 1] * &ys[ky - 1];
+    if executor::split_count(x.len()) > 1 {
+        executor::for_each(&mut products, |(a, b, r)| *r = &*a * &*b);
+        executor::verify(
+            "Toom-Cook products",
+            &products,
+            || {
+                let mut products = products.clone();
+                products.iter_mut().for_each(|(a, b, r)| *r = &*a * &*b);
+                products
+            },
+            (x, y),
+        );
+    } else {
+        products.iter_mut().for_each(|(a, b, r)| *r = &*a * &*b);
+    }
+
+    // r[j] = w(t_j) - w(inf) * t_j^d
+    let mut r: Vec<BigInt> = products
+        .into_iter()
+        .zip(&points)
+        .map(|((_, _, r), &t)| r - &w_inf * BigInt::from(t).pow(d as u32))
+        .collect();
+
+    // Divided differences, leaving r[j] = w[t_0, ..., t_j].
+    for k in 1..d {
+        for j in (k..d).rev() {
+            r[j] = (&r[j] - &r[j - 1]) / (points[j] - points[j - k]);
+        }
+    }
+
+    // Expand w(t) = r[0] + (t - t_0) * (r[1] + (t - t_1) * (r[2] + ...)) from the inside out.
+    let mut coeffs = vec![r[d - 1].clone()];
+    for k in (0..d - 1).rev() {
+        let mut next = vec![BigInt::ZERO; coeffs.len() + 1];
+        for (m, c) in coeffs.iter().enumerate() {
+            next[m + 1] += c;
+            next[m] -= c * points[k];
+        }
+        next[0] += &r[k];
+        coeffs = next;
+    }
+    coeffs.push(w_inf);
+
+    for (j, c) in coeffs.iter().enumerate() {
+        debug_assert!(c.sign() != Minus);
+        if c.sign() == Plus {
+            add2(&mut acc[i * j..], c.digits());
+        }
+    }
+}
+
+/// Two argument square accumulate:
+/// acc += x * x
+///
+/// This follows the same algorithms as `mac3`, but each can take advantage of the operands
+/// being equal, with roughly a third fewer digit products overall.
+fn sqr3(mut acc: &mut [BigDigit], mut x: &[BigDigit]) {
+    // Least-significant zeros only shift the output, by twice as many digits.
+    if let Some(&0) = x.first() {
+        if let Some(nz) = x.iter().position(|&d| d != 0) {
+            x = &x[nz..];
+            acc = &mut acc[nz * 2..];
+        } else {
+            return;
+        }
+    }
+    let acc = acc;
+    let n = x.len();
+
+    if n <= tuning::mul_karatsuba_threshold() {
+        // Long squaring: each cross product x[i] * x[j] with i < j is only computed once, and
+        // the sum of them doubled, before adding the squares x[i] * x[i] of the diagonal.
+        let mut prod = vec![0; n * 2];
+        for (i, &xi) in x.iter().enumerate().take(n - 1) {
+            mac_digit(&mut prod[i * 2 + 1..], &x[i + 1..], xi);
+        }
+
+        let mut carry = 0;
+        for d in prod.iter_mut() {
+            let top = *d >> (big_digit::BITS - 1);
+            *d = (*d << 1) | carry;
+            carry = top;
+        }
+
+        let mut diag = Vec::with_capacity(n * 2);
+        for &xi in x {
+            let (hi, lo) =
+                big_digit::from_doublebigdigit(DoubleBigDigit::from(xi) * DoubleBigDigit::from(xi));
+            diag.push(lo);
+            diag.push(hi);
+        }
+        add2(&mut prod, &diag);
+
+        while let Some(&0) = prod.last() {
+            prod.pop();
+        }
+        add2(acc, &prod);
+    } else if n <= tuning::mul_toom3_threshold() {
+        // Karatsuba squaring:
+        //
+        // With x = x0 + x1 * b, the middle term of the product in `mac3` becomes
+        // 2 * x0 * x1 = p0 + p2 - p1, where all three products are squares:
+        //
+        // p0 = x0 * x0
+        // p1 = (x1 - x0) * (x1 - x0)
+        // p2 = x1 * x1
+        //
+        // Since p1 is a square it can't be negative, so it is always subtracted.
+        let b = n / 2;
+        let (x0, x1) = x.split_at(b);
+
+        let len = x1.len() * 2 + 1;
+        let mut p = BigUint { data: vec![0; len] };
+
+        // p2 = x1 * x1
+        sqr3(&mut p.data, x1);
+        p.normalize();
+        add2(&mut acc[b..], &p.data);
+        add2(&mut acc[b * 2..], &p.data);
+
+        // p0 = x0 * x0
+        p.data.truncate(0);
+        p.data.resize(len, 0);
+        sqr3(&mut p.data, x0);
+        p.normalize();
+        add2(acc, &p.data);
+        add2(&mut acc[b..], &p.data);
+
+        // p1 = (x1 - x0) * (x1 - x0)
+        let (_, j) = sub_sign(x1, x0);
+        if !j.is_zero() {
+            p.data.truncate(0);
+            p.data.resize(len, 0);
+            sqr3(&mut p.data, &j.data);
+            p.normalize();
+            sub2(&mut acc[b..], &p.data);
+        }
+    } else if n > tuning::mul_ntt_threshold() {
+        sqr_ntt(acc, x);
+    } else {
+        // Toom-3 squaring, evaluating the same points as `mac3`, where every pointwise
+        // product is a square.
+        let i = n / 3 + 1;
+        let x0_len = Ord::min(n, i);
+        let x1_len = Ord::min(n - x0_len, i);
+
+        let x0 = bigint_from_slice(&x[..x0_len]);
+        let x1 = bigint_from_slice(&x[x0_len..x0_len + x1_len]);
+        let x2 = bigint_from_slice(&x[x0_len + x1_len..]);
+
+        let p = &x0 + &x2;
+        let p2 = &p - &x1;
+
+        // w(0), w(inf), w(1), w(-1) and w(-2)
+        let r0 = x0.square();
+        let r4 = x2.square();
+        let r1 = (p + x1).square();
+        let r2 = p2.square();
+        let r3: BigInt = ((p2 + x2) << 1) - x0;
+        let r3 = r3.square();
+
+        toom3_interpolate(acc, i, r0, r1, r2, r3, r4);
+    }
+}
+
+/// Returns `x * x`.
+pub(super) fn square(x: &BigUint) -> BigUint {
+    if x.data.len() <= 1 {
+        let d = x.data.first().map_or(0, |&d| DoubleBigDigit::from(d));
+        return BigUint::from(d * d);
+    }
+    let mut prod = BigUint {
+        data: vec![0; x.data.len() * 2 + 1],
+    };
+    sqr3(&mut prod.data, &x.data);
+    prod.normalized()
+}
+
+/// Writes `x * y` to `prod`, which must be exactly `x.len() + y.len()` digits long.
+pub(crate) fn mul_slices(prod: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) {
+    debug_assert_eq!(prod.len(), x.len() + y.len());
+    prod.fill(0);
+    if Ord::min(x.len(), y.len()) <= tuning::mul_karatsuba_threshold() {
+        // Long multiplication never carries past the length of the product, while the other
+        // algorithms may need a spare digit for intermediate values.
+        mac3(prod, x, y);
+    } else {
+        let p = mul3(x, y);
+        prod[..p.data.len()].copy_from_slice(&p.data);
+    }
+}
+
+/// Writes `x * x` to `prod`, which must be exactly `2 * x.len()` digits long.
+pub(crate) fn sqr_slices(prod: &mut [BigDigit], x: &[BigDigit]) {
+    debug_assert_eq!(prod.len(), x.len() * 2);
+    prod.fill(0);
+    if x.len() <= tuning::mul_karatsuba_threshold() {
+        sqr3(prod, x);
+    } else {
+        let p = square(&biguint_from_vec(x.to_vec()));
+        prod[..p.data.len()].copy_from_slice(&p.data);
+    }
+}
+
+/// Returns the low `n` digits of `x * y`, without normalizing them.
+///
+/// Large operands use Mulders' short product: with x and y split at k digits, only the low
+/// product x0 * y0 is computed in full, and the cross products x0 * y1 and x1 * y0 are themselves
+/// short products of n - k digits, while x1 * y1 doesn't contribute at all. Splitting at k = 0.7n
+/// saves about a fifth of a Karatsuba multiplication.
+pub(crate) fn mul_low_digits(x: &[BigDigit], y: &[BigDigit], n: usize) -> Vec<BigDigit> {
+    let x = &x[..Ord::min(x.len(), n)];
+    let y = &y[..Ord::min(y.len(), n)];
+    let mut low = vec![0; n];
+    if x.is_empty() || y.is_empty() {
+        return low;
+    }
+
+    // Short products save nothing over number-theoretic transforms.
+    if x.len() + y.len() <= n || Ord::min(x.len(), y.len()) > tuning::mul_ntt_threshold() {
+        let p = mul3(x, y);
+        let len = Ord::min(p.data.len(), n);
+        low[..len].copy_from_slice(&p.data[..len]);
+    } else if Ord::min(x.len(), y.len()) <= tuning::mul_karatsuba_threshold() {
+        // Long multiplication, leaving out the digit products entirely above n. The ones left
+        // sum to less than n * B^(n + 1), so two spare digits catch their carries.
+        low.resize(n + 2, 0);
+        for (i, &xi) in x.iter().enumerate() {
+            let yi = &y[..Ord::min(y.len(), n - i)];
+            mac_digit(&mut low[i..], yi, xi);
+        }
+        low.truncate(n);
+    } else {
+        let k = Ord::max(n * 7 / 10, n / 2 + 1);
+        let (x0, x1) = x.split_at(Ord::min(x.len(), k));
+        let (y0, y1) = y.split_at(Ord::min(y.len(), k));
+
+        let p = mul3(x0, y0);
+        let len = Ord::min(p.data.len(), n);
+        low[..len].copy_from_slice(&p.data[..len]);
+        // Carries out of the top digit are dropped, as the result is modulo B^n.
+        __add2(&mut low[k..], &mul_low_digits(x1, y0, n - k));
+        __add2(&mut low[k..], &mul_low_digits(x0, y1, n - k));
+    }
+    low
+}
+
+/// Returns `x * y` divided by B^n and rounded down.
+///
+/// This first finds floor(x * y / B^t) for t = n - 2 to within `err` units with `approx_high`,
+/// which leaves out most of the digit products below B^t. Unless those two guard digits are so
+/// close to overflowing that the error could carry into the result, dropping them gives the exact
+/// result. Otherwise the full product is used instead, which is very unlikely for random inputs.
+pub(crate) fn mul_high_digits(x: &[BigDigit], y: &[BigDigit], n: usize) -> BigUint {
+    if n <= 2 || x.len() + y.len() <= n + 1 {
+        return shr_digits(mul3(x, y), n);
+    }
+    let t = n - 2;
+    let (approx, err) = approx_high(x, y, t);
+    let digit = |i| approx.data.get(i).copied().unwrap_or(0);
+    let guard = big_digit::to_doublebigdigit(digit(1), digit(0));
+    match guard.checked_add(err as DoubleBigDigit) {
+        Some(_) => shr_digits(approx, 2),
+        None => shr_digits(mul3(x, y), n),
+    }
+}
+
+fn shr_digits(mut x: BigUint, n: usize) -> BigUint {
+    x.data.drain(..Ord::min(n, x.data.len()));
+    x
+}
+
+/// Returns a and err with floor(x * y / B^t) - err <= a <= floor(x * y / B^t), for t > 0.
+///
+/// This is Mulders' short product again, turned around to keep the high digits: with the top h
+/// digits x1 and y1 split off, x1 * y1 is computed in full, the cross products are themselves
+/// approximate, and x0 * y0 is small enough to be left out.
+fn approx_high(x: &[BigDigit], y: &[BigDigit], t: usize) -> (BigUint, usize) {
+    let (x, y) = if x.len() <= y.len() { (x, y) } else { (y, x) };
+    if x.is_empty() {
+        return (BigUint::ZERO, 0);
+    }
+
+    if x.len() <= tuning::mul_karatsuba_threshold() {
+        // Long multiplication, leaving out the digit products below B^(t - 1). Those of each
+        // digit of x sum to less than B^t, so each leaves an error of at most one unit.
+        let mut acc = vec![0; x.len() + y.len() + 1];
+        let mut err = 0;
+        for (i, &xi) in x.iter().enumerate() {
+            let start = (t - 1).saturating_sub(i);
+            if start > 0 {
+                err += 1;
+            }
+            if start < y.len() {
+                mac_digit(&mut acc[i + start..], &y[start..], xi);
             }
         }
+        return (shr_digits(biguint_from_vec(acc), t), err);
+    } else if x.len() > tuning::mul_ntt_threshold() {
+        return (shr_digits(mul3(x, y), t), 0);
+    }
+
+    // The high product has m digits, and x0 * y0 < B^t must hold to leave it out, so x1 and y1
+    // need m digits between them. If x is too short for half of them, y1 takes the rest.
+    let m = x.len() + y.len() - t;
+    let h = Ord::max(m * 7 / 10, m.div_ceil(2));
+    let hx = Ord::min(h, x.len());
+    let hy = Ord::min(Ord::max(h, m - hx), y.len());
+    let (x0, x1) = x.split_at(x.len() - hx);
+    let (y0, y1) = y.split_at(y.len() - hy);
+
+    // x1 * y1 is at offset x0.len() + y0.len() <= t, and each term loses less than a unit to
+    // rounding down, as does leaving out x0 * y0.
+    let mut approx = shr_digits(mul3(x1, y1), t - x0.len() - y0.len());
+    let mut err = 2;
+    for (a, b, offset) in [(x1, y0, x0.len()), (x0, y1, y0.len())] {
+        if offset >= t {
+            approx += mul3(a, b) << ((offset - t) * big_digit::BITS as usize);
+        } else {
+            let (p, e) = approx_high(a, b, t - offset);
+            approx += p;
+            err += e + 1;
+        }
+    }
+    (approx, err)
+}
+
+/// acc += x * y, accumulating the product directly into acc.
+fn add_mul_slices(acc: &mut BigUint, x: &[BigDigit], y: &[BigDigit]) {
+    if x.is_empty() || y.is_empty() {
+        return;
+    }
+    let len = Ord::max(acc.data.len(), x.len() + y.len()) + 1;
+    acc.data.resize(len, 0);
+    mac3(&mut acc.data, x, y);
+    acc.normalize();
+}
+
+/// acc = |acc - x * y|, returning whether the product was larger.
+fn sub_mul_slices(acc: &mut BigUint, x: &[BigDigit], y: &[BigDigit]) -> bool {
+    if x.is_empty() || y.is_empty() {
+        return false;
+    }
+    let (x, y) = if x.len() <= y.len() { (x, y) } else { (y, x) };
+    // One more digit than the product needs, for the last borrow of `msb_digit`.
+    let len = Ord::max(acc.data.len(), x.len() + y.len() + 1);
+    acc.data.resize(len, 0);
+
+    let wrapped = if x.len() <= tuning::mul_karatsuba_threshold() {
+        // Long multiplication, one row at a time. The rows only ever decrease acc, so once it
+        // wraps around it can't wrap back, and at most one row borrows out of the top.
+        let mut wrapped = false;
+        for (i, &xi) in x.iter().enumerate() {
+            wrapped |= msb_digit(&mut acc.data[i..], y, xi);
+        }
+        wrapped
+    } else {
+        // The faster algorithms need the product as a separate number anyway.
+        __sub2(&mut acc.data, &mul3(x, y).data) != 0
+    };
+
+    if wrapped {
+        // acc holds B^len - (x * y - acc), so negating it modulo B^len leaves the difference.
+        for d in acc.data.iter_mut() {
+            *d = !*d;
+        }
+        __add2(&mut acc.data, &[1]);
     }
+    acc.normalize();
+    wrapped
+}
+
+/// acc += a * b
+pub(crate) fn add_mul(acc: &mut BigUint, a: &BigUint, b: &BigUint) {
+    add_mul_slices(acc, &a.data, &b.data);
+}
+
+/// acc = |acc - a * b|, returning whether the product was larger.
+pub(crate) fn sub_mul(acc: &mut BigUint, a: &BigUint, b: &BigUint) -> bool {
+    sub_mul_slices(acc, &a.data, &b.data)
+}
+
+/// acc += a * b
+pub(crate) fn add_mul_digit(acc: &mut BigUint, a: &BigUint, b: BigDigit) {
+    add_mul_slices(acc, &a.data, &[b]);
+}
+
+/// acc = |acc - a * b|, returning whether the product was larger.
+pub(crate) fn sub_mul_digit(acc: &mut BigUint, a: &BigUint, b: BigDigit) -> bool {
+    sub_mul_slices(acc, &a.data, &[b])
 }
 
 fn mul3(x: &[BigDigit], y: &[BigDigit]) -> BigUint {
@@ -606,6 +1102,44 @@ impl CheckedMul for BigUint {
 
 impl_product_iter_type!(BigUint);
 
+impl MulAdd<BigUint, BigUint> for BigUint {
+    type Output = BigUint;
+
+    #[inline]
+    fn mul_add(self, a: BigUint, mut b: BigUint) -> BigUint {
+        add_mul(&mut b, &self, &a);
+        b
+    }
+}
+
+impl MulAdd<&BigUint, &BigUint> for &BigUint {
+    type Output = BigUint;
+
+    #[inline]
+    fn mul_add(self, a: &BigUint, b: &BigUint) -> BigUint {
+        let mut acc = b.clone();
+        add_mul(&mut acc, self, a);
+        acc
+    }
+}
+
+impl MulAddAssign<BigUint, BigUint> for BigUint {
+    #[inline]
+    fn mul_add_assign(&mut self, a: BigUint, mut b: BigUint) {
+        add_mul(&mut b, self, &a);
+        *self = b;
+    }
+}
+
+impl MulAddAssign<&BigUint, &BigUint> for BigUint {
+    #[inline]
+    fn mul_add_assign(&mut self, a: &BigUint, b: &BigUint) {
+        let mut acc = b.clone();
+        add_mul(&mut acc, self, a);
+        *self = acc;
+    }
+}
+
 #[test]
 fn test_sub_sign() {
     use crate::BigInt;
@@ -624,3 +1158,135 @@ fn test_sub_sign() {
     assert_eq!(sub_sign_i(&a.data, &b.data), &a_i - &b_i);
     assert_eq!(sub_sign_i(&b.data, &a.data), &b_i - &a_i);
 }
+
+#[test]
+fn test_square() {
+    use super::TestRng;
+
+    let mut rng = TestRng::new(0x2545_f491);
+
+    for len in [0, 1, 2, 3, 10, 31, 32, 33, 64, 100, 255, 257, 600] {
+        let x = biguint_from_vec(rng.digits(len));
+        assert_eq!(square(&x), mul3(&x.data, &x.data), "{} digits", len);
+
+        let ones = biguint_from_vec(vec![BigDigit::MAX; len]);
+        assert_eq!(square(&ones), mul3(&ones.data, &ones.data), "{} ones", len);
+
+        // Low zero digits shift the square.
+        let shifted = &x << 100;
+        assert_eq!(square(&shifted), square(&x) << 200);
+    }
+}
+
+#[test]
+fn test_toom() {
+    use super::TestRng;
+
+    // Reference long multiplication.
+    fn schoolbook(x: &[BigDigit], y: &[BigDigit]) -> Vec<BigDigit> {
+        let mut acc = vec![0; x.len() + y.len() + 1];
+        for (i, &xi) in x.iter().enumerate() {
+            mac_digit(&mut acc[i..], y, xi);
+        }
+        acc
+    }
+
+    let mut rng = TestRng::new(0x9e37_79b9_7f4a_7c15);
+
+    for &(kx, ky) in &[(2, 3), (2, 4), (3, 4), (4, 4), (3, 3)] {
+        for &(xl, yl) in &[(8, 12), (10, 19), (40, 80), (33, 44), (50, 50), (7, 30)] {
+            for all_ones in [false, true] {
+                let mut digits = |len| {
+                    if all_ones {
+                        vec![BigDigit::MAX; len]
+                    } else {
+                        rng.digits(len)
+                    }
+                };
+                let (x, y) = (digits(xl), digits(yl));
+                let mut acc = vec![0; xl + yl + 1];
+                toom(&mut acc, &x, &y, kx, ky);
+                assert_eq!(acc, schoolbook(&x, &y), "Toom-{}/{} {}x{}", ky, kx, xl, yl);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mul_add() {
+    use super::TestRng;
+
+    let mut rng = TestRng::new(0x9e37_79b9_7f4a_7c15);
+    let mut number = |len| biguint_from_vec(rng.digits(len));
+
+    for &(cl, al, bl) in &[(0, 3, 4), (9, 3, 4), (7, 1, 5), (3, 40, 50), (100, 40, 50)] {
+        let (c, a, b) = (number(cl), number(al), number(bl));
+        let p = &a * &b;
+
+        let mut acc = c.clone();
+        acc.add_mul_assign(&a, &b);
+        assert_eq!(acc, &c + &p);
+        acc.sub_mul_assign(&a, &b);
+        assert_eq!(acc, c);
+        assert_eq!((&a).mul_add(&b, &c), &c + &p);
+
+        // The difference has the sign of the larger side.
+        let mut diff = c.clone();
+        let larger = sub_mul(&mut diff, &a, &b);
+        assert_eq!(larger, p > c);
+        assert_eq!(diff, if larger { &p - &c } else { &c - &p });
+
+        let mut acc = c.clone();
+        acc.add_mul_digit_assign(&a, 12345);
+        assert_eq!(acc, &c + &a * 12345u32);
+        let mut diff = c.clone();
+        let larger = sub_mul_digit(&mut diff, &a, 12345);
+        assert_eq!(
+            diff,
+            if larger {
+                &a * 12345u32 - &c
+            } else {
+                &c - &a * 12345u32
+            }
+        );
+    }
+}
+
+#[test]
+fn test_mul_low_high() {
+    use super::TestRng;
+
+    let mut rng = TestRng::new(0x2545_f491_4f6c_dd1d);
+    let mut number = |len| rng.digits(len);
+    let ones = |len| vec![big_digit::MAX; len];
+
+    let mut cases = Vec::new();
+    for &(xl, yl) in &[(3, 5), (40, 40), (37, 300), (150, 170), (400, 400)] {
+        cases.push((number(xl), number(yl)));
+        cases.push((ones(xl), ones(yl)));
+    }
+    for (x, y) in &cases {
+        let p = mul3(x, y);
+        let len = x.len() + y.len();
+        for n in [
+            1,
+            2,
+            3,
+            len / 3,
+            len / 2,
+            len / 2 + 1,
+            len * 2 / 3,
+            len - 1,
+            len,
+            len + 5,
+        ] {
+            let low = biguint_from_vec(mul_low_digits(x, y, n));
+            let expected = biguint_from_vec(p.data[..Ord::min(n, p.data.len())].to_vec());
+            assert_eq!(low, expected, "{} {} {}", x.len(), y.len(), n);
+
+            let high = mul_high_digits(x, y, n);
+            let expected = &p >> (n * big_digit::BITS as usize);
+            assert_eq!(high, expected, "{} {} {}", x.len(), y.len(), n);
+        }
+    }
+}
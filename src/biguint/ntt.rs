@@ -0,0 +1,435 @@
+//! Multiplication by number-theoretic transforms, for the largest operands.
+//!
+//! The operands are split into 64-bit coefficients and convolved modulo three primes of the form
+//! `k * 2^s + 1` just below 2^63, which support transforms of up to 2^55 points. Each coefficient
+//! of the exact convolution is less than `n * 2^128`, well below the product of the three primes,
+//! so it is recovered from its residues with the Chinese remainder theorem (Garner's algorithm).
+//!
+//! All arithmetic modulo a prime uses Montgomery multiplication with `R = 2^64`. The forward
+//! transform is decimation-in-frequency, leaving its output in bit-reversed order, and the
+//! inverse is decimation-in-time, taking bit-reversed input, so no permutation is ever needed.
+
+use alloc::vec::Vec;
+
+use super::addition::add2;
+
+use crate::big_digit::BigDigit;
+use crate::executor;
+
+struct Prime {
+    p: u64,
+    /// `-p^-1 mod 2^64`
+    pinv: u64,
+    /// `R^2 mod p`
+    r2: u64,
+    /// A generator of the multiplicative group.
+    generator: u64,
+    /// The largest supported transform length is `2^max_log`.
+    max_log: u32,
+}
+
+impl Prime {
+    const fn new(p: u64, generator: u64, max_log: u32) -> Self {
+        // Newton's iteration doubles the number of correct low bits of `p^-1` every step.
+        let mut inv = p;
+        let mut i = 0;
+        while i < 5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(p.wrapping_mul(inv)));
+            i += 1;
+        }
+        let r = (1u128 << 64) % p as u128;
+        Prime {
+            p,
+            pinv: inv.wrapping_neg(),
+            r2: (r * r % p as u128) as u64,
+            generator,
+            max_log,
+        }
+    }
+
+    /// Returns `t * R^-1 mod p`, for `t < p * 2^64`.
+    #[inline]
+    fn redc(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.pinv);
+        let u = ((t + m as u128 * self.p as u128) >> 64) as u64;
+        if u >= self.p {
+            u - self.p
+        } else {
+            u
+        }
+    }
+
+    /// Returns `a * b * R^-1 mod p`, for any `a` and `b < p`.
+    #[inline]
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        self.redc(a as u128 * b as u128)
+    }
+
+    #[inline]
+    fn add(&self, a: u64, b: u64) -> u64 {
+        let s = a + b;
+        if s >= self.p {
+            s - self.p
+        } else {
+            s
+        }
+    }
+
+    #[inline]
+    fn sub(&self, a: u64, b: u64) -> u64 {
+        if a >= b {
+            a - b
+        } else {
+            a + self.p - b
+        }
+    }
+
+    /// Converts `a` to Montgomery form, `a * R mod p`.
+    #[inline]
+    fn to_mont(&self, a: u64) -> u64 {
+        self.mul(a, self.r2)
+    }
+
+    /// Returns `base^exp`, where `base` and the result are in Montgomery form.
+    fn pow(&self, mut base: u64, mut exp: u64) -> u64 {
+        let mut acc = self.to_mont(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = self.mul(acc, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Returns the inverse of `a`, where both are in Montgomery form.
+    fn inv(&self, a: u64) -> u64 {
+        self.pow(a, self.p - 2)
+    }
+
+    /// Returns `[1, w, w^2, ...]` in Montgomery form, `len` powers in all.
+    fn powers(&self, w: u64, len: usize) -> Vec<u64> {
+        let mut powers = Vec::with_capacity(len);
+        let mut x = self.to_mont(1);
+        for _ in 0..len {
+            powers.push(x);
+            x = self.mul(x, w);
+        }
+        powers
+    }
+
+    /// The butterflies of one decimation-in-frequency stage, for the pairs `(lo[j], hi[j])`
+    /// with twiddle index `(start + j) * stride`.
+    fn dif_butterflies(
+        &self,
+        lo: &mut [u64],
+        hi: &mut [u64],
+        tw: &[u64],
+        stride: usize,
+        start: usize,
+    ) {
+        for (j, (u, v)) in lo.iter_mut().zip(hi.iter_mut()).enumerate() {
+            let (x, y) = (*u, *v);
+            *u = self.add(x, y);
+            *v = self.mul(self.sub(x, y), tw[(start + j) * stride]);
+        }
+    }
+
+    /// The butterflies of one decimation-in-time stage, see `dif_butterflies`.
+    fn dit_butterflies(
+        &self,
+        lo: &mut [u64],
+        hi: &mut [u64],
+        tw: &[u64],
+        stride: usize,
+        start: usize,
+    ) {
+        for (j, (u, v)) in lo.iter_mut().zip(hi.iter_mut()).enumerate() {
+            let x = *u;
+            let y = self.mul(*v, tw[(start + j) * stride]);
+            *u = self.add(x, y);
+            *v = self.sub(x, y);
+        }
+    }
+
+    /// Runs one stage of butterflies over the two halves of `a`, split across the executor if
+    /// it is long enough.
+    fn stage(&self, a: &mut [u64], tw: &[u64], stride: usize, dif: bool, parallel: bool) {
+        let half = a.len() / 2;
+        let (lo, hi) = a.split_at_mut(half);
+        let tasks = if parallel {
+            executor::split_count(half)
+        } else {
+            1
+        };
+        let butterflies = |lo: &mut [u64], hi: &mut [u64], start: usize| {
+            if dif {
+                self.dif_butterflies(lo, hi, tw, stride, start);
+            } else {
+                self.dit_butterflies(lo, hi, tw, stride, start);
+            }
+        };
+        if tasks > 1 {
+            let size = half.div_ceil(tasks);
+            let mut pieces: Vec<_> = lo
+                .chunks_mut(size)
+                .zip(hi.chunks_mut(size))
+                .enumerate()
+                .map(|(i, (lo, hi))| (lo, hi, i * size))
+                .collect();
+            executor::for_each(&mut pieces, |(lo, hi, start)| butterflies(lo, hi, *start));
+        } else {
+            butterflies(lo, hi, 0);
+        }
+    }
+
+    /// Forward transform of `a` in place, with twiddles `tw[j * stride]`.
+    fn forward(&self, a: &mut [u64], tw: &[u64], stride: usize, parallel: bool) {
+        let n = a.len();
+        if parallel && executor::split_count(n) > 1 {
+            self.stage(a, tw, stride, true, true);
+            let (lo, hi) = a.split_at_mut(n / 2);
+            executor::join(
+                || self.forward(lo, tw, stride * 2, true),
+                || self.forward(hi, tw, stride * 2, true),
+            );
+            return;
+        }
+
+        let (mut len, mut stride) = (n, stride);
+        while len >= 2 {
+            for block in a.chunks_exact_mut(len) {
+                let (lo, hi) = block.split_at_mut(len / 2);
+                self.dif_butterflies(lo, hi, tw, stride, 0);
+            }
+            len /= 2;
+            stride *= 2;
+        }
+    }
+
+    /// Inverse transform of `a` in place, without the final scaling by `1/n`.
+    fn inverse(&self, a: &mut [u64], tw: &[u64], stride: usize, parallel: bool) {
+        let n = a.len();
+        if parallel && executor::split_count(n) > 1 {
+            let (lo, hi) = a.split_at_mut(n / 2);
+            executor::join(
+                || self.inverse(lo, tw, stride * 2, true),
+                || self.inverse(hi, tw, stride * 2, true),
+            );
+            self.stage(a, tw, stride, false, true);
+            return;
+        }
+
+        let (mut len, mut stride) = (2, stride * n / 2);
+        while len <= n {
+            for block in a.chunks_exact_mut(len) {
+                let (lo, hi) = block.split_at_mut(len / 2);
+                self.dit_butterflies(lo, hi, tw, stride, 0);
+            }
+            len *= 2;
+            stride /= 2;
+        }
+    }
+
+    /// Returns the cyclic convolution of `x` and `y` modulo `p`, using `n`-point transforms.
+    /// If `y` is `None`, this is the convolution of `x` with itself, which needs one less
+    /// forward transform.
+    fn convolve(&self, x: &[u64], y: Option<&[u64]>, n: usize, parallel: bool) -> Vec<u64> {
+        let log = n.trailing_zeros();
+        assert!(
+            log <= self.max_log,
+            "operands too large for NTT multiplication"
+        );
+
+        let g = self.to_mont(self.generator);
+        let w = self.pow(g, (self.p - 1) >> log);
+        let tw = self.powers(w, n / 2);
+        let itw = self.powers(self.inv(w), n / 2);
+
+        let reduce = |x: &[u64]| {
+            let mut a: Vec<u64> = x.iter().map(|&c| c % self.p).collect();
+            a.resize(n, 0);
+            a
+        };
+        let mut a = reduce(x);
+        // Pointwise products pick up a factor of R^-1, which is cancelled when scaling by 1/n.
+        if let Some(y) = y {
+            let mut b = reduce(y);
+            if parallel {
+                executor::join(
+                    || self.forward(&mut a, &tw, 1, true),
+                    || self.forward(&mut b, &tw, 1, true),
+                );
+            } else {
+                self.forward(&mut a, &tw, 1, false);
+                self.forward(&mut b, &tw, 1, false);
+            }
+            for (a, &b) in a.iter_mut().zip(b.iter()) {
+                *a = self.mul(*a, b);
+            }
+        } else {
+            self.forward(&mut a, &tw, 1, parallel);
+            for a in a.iter_mut() {
+                *a = self.mul(*a, *a);
+            }
+        }
+        self.inverse(&mut a, &itw, 1, parallel);
+
+        // n | p - 1, and n * (p - (p - 1) / n) = 1 mod p.
+        let scale = self.to_mont(self.to_mont(self.p - (self.p - 1) / n as u64));
+        for a in a.iter_mut() {
+            *a = self.mul(*a, scale);
+        }
+        a
+    }
+}
+
+static PRIMES: [Prime; 3] = [
+    Prime::new(0x5700_0000_0000_0001, 5, 56),
+    Prime::new(0x4180_0000_0000_0001, 3, 55),
+    Prime::new(0x6280_0000_0000_0001, 3, 55),
+];
+
+/// Recombines the residues of each coefficient into the 64-bit digits of the product.
+fn recombine(r1: &[u64], r2: &[u64], r3: &[u64], len: usize) -> Vec<u64> {
+    let [m1, m2, m3] = &PRIMES;
+    let (p1, p2, p3) = (m1.p, m2.p, m3.p);
+
+    // Montgomery forms of p1^-1 mod p2, p1 mod p3, and (p1 * p2)^-1 mod p3.
+    let inv12 = m2.inv(m2.to_mont(p1 % p2));
+    let p1_3 = m3.to_mont(p1 % p3);
+    let inv123 = m3.inv(m3.mul(p1_3, m3.to_mont(p2 % p3)));
+    let p12 = p1 as u128 * p2 as u128;
+    let (p12_lo, p12_hi) = (p12 as u64 as u128, p12 >> 64);
+
+    let mut digits = Vec::with_capacity(len + 2);
+    let mut carry = 0u128;
+    for i in 0..len {
+        let (a1, a2, a3) = (r1[i], r2[i], r3[i]);
+        // x12 = a1 + p1 * t2 is the residue modulo p1 * p2.
+        let t2 = m2.mul(m2.sub(a2, a1 % p2), inv12);
+        let x12 = a1 as u128 + p1 as u128 * t2 as u128;
+        let x12_3 = m3.add(a1 % p3, m3.mul(t2, p1_3));
+        let t3 = m3.mul(m3.sub(a3, x12_3), inv123);
+
+        // x = x12 + p1 * p2 * t3, held as lo + hi * 2^128.
+        let a = p12_lo * t3 as u128;
+        let b = p12_hi * t3 as u128;
+        let (lo, c1) = x12.overflowing_add(a);
+        let (lo, c2) = lo.overflowing_add(b << 64);
+        let hi = (b >> 64) as u64 + c1 as u64 + c2 as u64;
+
+        let (lo, c3) = lo.overflowing_add(carry);
+        digits.push(lo as u64);
+        carry = (lo >> 64) + ((hi as u128 + c3 as u128) << 64);
+    }
+    digits.push(carry as u64);
+    digits.push((carry >> 64) as u64);
+    digits
+}
+
+/// Returns the product of `x` and `y`, or the square of `x` if `y` is `None`, as 64-bit digits.
+fn product(x: &[u64], y: Option<&[u64]>, parallel: bool) -> Vec<u64> {
+    let len = x.len() + y.unwrap_or(x).len() - 1;
+    let n = len.next_power_of_two();
+    let mut residues: Vec<_> = PRIMES.iter().map(|m| (m, Vec::new())).collect();
+    let convolve = |(m, r): &mut (&Prime, Vec<u64>)| *r = m.convolve(x, y, n, parallel);
+    if parallel {
+        executor::for_each(&mut residues, convolve);
+    } else {
+        residues.iter_mut().for_each(convolve);
+    }
+    recombine(&residues[0].1, &residues[1].1, &residues[2].1, len)
+}
+
+cfg_digit!(
+    fn to_u64_digits(x: &[BigDigit]) -> Vec<u64> {
+        x.chunks(2)
+            .map(|c| u64::from(c[0]) | u64::from(c.get(1).copied().unwrap_or(0)) << 32)
+            .collect()
+    }
+
+    fn to_u64_digits(x: &[BigDigit]) -> Vec<u64> {
+        x.to_vec()
+    }
+);
+
+cfg_digit!(
+    fn from_u64_digits(x: Vec<u64>) -> Vec<BigDigit> {
+        x.iter()
+            .flat_map(|&d| [d as u32, (d >> 32) as u32])
+            .collect()
+    }
+
+    fn from_u64_digits(x: Vec<u64>) -> Vec<BigDigit> {
+        x
+    }
+);
+
+/// Three argument multiply accumulate, acc += x * y, using NTT multiplication.
+pub(super) fn mac_ntt(acc: &mut [BigDigit], x: &[BigDigit], y: &[BigDigit]) {
+    mac_ntt_impl(acc, x, Some(y));
+}
+
+/// Two argument square accumulate, acc += x * x, using NTT multiplication.
+pub(super) fn sqr_ntt(acc: &mut [BigDigit], x: &[BigDigit]) {
+    mac_ntt_impl(acc, x, None);
+}
+
+fn mac_ntt_impl(acc: &mut [BigDigit], x: &[BigDigit], y: Option<&[BigDigit]>) {
+    let x64 = to_u64_digits(x);
+    let y64 = y.map(to_u64_digits);
+    let y64 = y64.as_deref();
+    let parallel = executor::split_count(x64.len() + y64.unwrap_or(&x64).len()) > 1;
+    let prod = product(&x64, y64, parallel);
+    if parallel {
+        executor::verify(
+            "NTT multiplication",
+            &prod,
+            || product(&x64, y64, false),
+            (x, y),
+        );
+    }
+
+    let mut prod = from_u64_digits(prod);
+    while let Some(&0) = prod.last() {
+        prod.pop();
+    }
+    add2(acc, &prod);
+}
+
+#[test]
+fn test_ntt_product() {
+    use super::{BigUint, TestRng};
+
+    let mut rng = TestRng::new(0x9e37_79b9_7f4a_7c15);
+
+    for &(xl, yl) in &[(1, 1), (1, 7), (3, 5), (16, 16), (17, 40), (100, 333)] {
+        for all_ones in [false, true] {
+            let mut words = |len| {
+                if all_ones {
+                    vec![!0; len]
+                } else {
+                    rng.words(len)
+                }
+            };
+            let (x, y) = (words(xl), words(yl));
+            let expected =
+                BigUint::from_slice(&u64_to_u32(&x)) * BigUint::from_slice(&u64_to_u32(&y));
+            for parallel in [false, true] {
+                let prod = product(&x, Some(&y), parallel);
+                assert_eq!(BigUint::from_slice(&u64_to_u32(&prod)), expected);
+            }
+            let square = product(&x, None, false);
+            let x = BigUint::from_slice(&u64_to_u32(&x));
+            assert_eq!(BigUint::from_slice(&u64_to_u32(&square)), &x * &x);
+        }
+    }
+
+    fn u64_to_u32(x: &[u64]) -> Vec<u32> {
+        x.iter()
+            .flat_map(|&d| [d as u32, (d >> 32) as u32])
+            .collect()
+    }
+}
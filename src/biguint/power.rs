@@ -72,7 +72,7 @@ macro_rules! pow_impl {
                 let mut base = self;
 
                 while exp & 1 == 0 {
-                    base = &base * &base;
+                    base = base.square();
                     exp >>= 1;
                 }
 
@@ -83,7 +83,7 @@ macro_rules! pow_impl {
                 let mut acc = base.clone();
                 while exp > 1 {
                     exp >>= 1;
-                    base = &base * &base;
+                    base = base.square();
                     if exp & 1 == 1 {
                         acc *= &base;
                     }
@@ -160,14 +160,14 @@ fn plain_modpow(base: &BigUint, exp_data: &[BigDigit], modulus: &BigUint) -> Big
     let mut base = base % modulus;
     for _ in 0..i {
         for _ in 0..big_digit::BITS {
-            base = &base * &base % modulus;
+            base = base.square() % modulus;
         }
     }
 
     let mut r = exp_data[i];
     let mut b = 0u8;
     while r.is_even() {
-        base = &base * &base % modulus;
+        base = base.square() % modulus;
         r >>= 1;
         b += 1;
     }
@@ -183,7 +183,7 @@ fn plain_modpow(base: &BigUint, exp_data: &[BigDigit], modulus: &BigUint) -> Big
 
     {
         let mut unit = |exp_is_odd| {
-            base = &base * &base % modulus;
+            base = base.square() % modulus;
             if exp_is_odd {
                 acc *= &base;
                 acc %= modulus;
@@ -0,0 +1,740 @@
+use super::monty::MontyContext;
+use super::roots::is_square;
+use super::sieve::{WheelSieve, WHEEL};
+use super::{BigUint, DigitDivisor};
+
+use crate::executor;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use num_integer::Integer;
+use num_traits::{CheckedSub, One, ToPrimitive, Zero};
+
+/// Primes below this bound are tried as divisors before any probable-prime test.
+const TRIAL_DIVISION_BOUND: u64 = 1000;
+
+/// The primes below `TRIAL_DIVISION_BOUND`, built once at compile time.
+pub(super) const SMALL_PRIMES: [u64; 168] = small_primes();
+
+/// Miller–Rabin rounds only run in parallel from this size, below which a round is too quick to
+/// be worth a task of its own.
+const PARALLEL_ROUNDS_BITS: u64 = 512;
+
+/// Segments are sieved by the primes up to this bound, or up to the square root of the end of
+/// the range if that is smaller.
+const SIEVE_BOUND: u64 = 1 << 16;
+
+/// Segments are sieved, and their candidates tested, as separate tasks from this size.
+const PARALLEL_SIEVE_BITS: u64 = 128;
+
+/// Bases for which the strong probable-prime test is exact for every `u64`.
+const U64_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// The outcome of trial division.
+enum Trial {
+    Prime,
+    Composite,
+    Unknown,
+}
+
+/// Returns the primes below `TRIAL_DIVISION_BOUND`, by trial division by the ones before.
+const fn small_primes() -> [u64; 168] {
+    let mut primes = [0; 168];
+    let mut len = 0;
+    let mut n = 2;
+    while n < TRIAL_DIVISION_BOUND {
+        let mut i = 0;
+        while i < len && primes[i] * primes[i] <= n && n % primes[i] > 0 {
+            i += 1;
+        }
+        if i == len || primes[i] * primes[i] > n {
+            primes[len] = n;
+            len += 1;
+        }
+        n += 1;
+    }
+    assert!(len == primes.len());
+    primes
+}
+
+/// Tries dividing `n` by the primes below `TRIAL_DIVISION_BOUND`.
+///
+/// The primes are grouped into products that fit in a `u64`, so that each group costs one pass
+/// over the digits of `n`, and the primes of the group then divide the remainder.
+fn trial_division(n: &BigUint) -> Trial {
+    if let Some(n) = n.to_u64() {
+        if n < TRIAL_DIVISION_BOUND * TRIAL_DIVISION_BOUND {
+            return if n >= 2
+                && SMALL_PRIMES
+                    .iter()
+                    .take_while(|&&p| p * p <= n)
+                    .all(|&p| !Integer::is_multiple_of(&n, &p))
+            {
+                Trial::Prime
+            } else {
+                Trial::Composite
+            };
+        }
+    }
+
+    let mut start = 0;
+    let mut product = 1u64;
+    for (i, &p) in SMALL_PRIMES.iter().enumerate() {
+        product *= p;
+        let next = SMALL_PRIMES.get(i + 1).copied().unwrap_or(u64::MAX);
+        if product.checked_mul(next).is_none() {
+            let r = DigitDivisor::new(product).rem(n);
+            if SMALL_PRIMES[start..=i]
+                .iter()
+                .any(|&p| Integer::is_multiple_of(&r, &p))
+            {
+                return Trial::Composite;
+            }
+            start = i + 1;
+            product = 1;
+        }
+    }
+    Trial::Unknown
+}
+
+#[inline]
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(m)) as u64
+}
+
+/// Returns whether the odd `n > 2` is a strong probable prime to `base`.
+fn strong_probable_prime_u64(n: u64, base: u64) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let base = base % n;
+    if base == 0 {
+        return true;
+    }
+
+    let mut x = 1;
+    let mut b = base;
+    let mut e = d;
+    while e > 0 {
+        if e & 1 == 1 {
+            x = mul_mod(x, b, n);
+        }
+        b = mul_mod(b, b, n);
+        e >>= 1;
+    }
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns whether the odd `n > 2` is prime, by strong probable-prime tests to the first twelve
+/// prime bases, which no composite below 2^64 passes.
+fn is_prime_u64(n: u64) -> bool {
+    U64_BASES
+        .iter()
+        .all(|&base| strong_probable_prime_u64(n, base))
+}
+
+/// The strong probable-prime test for one odd `n`, sharing its Montgomery context and the
+/// decomposition `n - 1 = d * 2^s` between bases.
+struct MillerRabin<'a> {
+    n: &'a BigUint,
+    n_minus_1: BigUint,
+    d: BigUint,
+    s: u64,
+    monty: MontyContext<'a>,
+}
+
+impl<'a> MillerRabin<'a> {
+    fn new(n: &'a BigUint) -> Self {
+        let n_minus_1 = n - 1u32;
+        let s = n_minus_1.trailing_zeros().unwrap();
+        MillerRabin {
+            n,
+            d: &n_minus_1 >> s,
+            n_minus_1,
+            s,
+            monty: MontyContext::new(n),
+        }
+    }
+
+    /// Returns whether `n` is a strong probable prime to `base`.
+    fn test(&self, base: &BigUint) -> bool {
+        let mut x = self.monty.modpow(base, &self.d);
+        if x.is_one() || x == self.n_minus_1 {
+            return true;
+        }
+        for _ in 1..self.s {
+            x = x.square() % self.n;
+            if x == self.n_minus_1 {
+                return true;
+            }
+            if x.is_one() {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+/// Returns `rounds` bases for Miller–Rabin on `n`, starting with 2, all from 2 to `n - 2`.
+///
+/// The other bases come from a SplitMix64 sequence seeded with the low digits of `n`, so they are
+/// repeatable. Being a public function of `n`, they can be computed for any candidate, so a
+/// composite could be built to pass them; the test is not meant for adversarial input.
+fn bases(n: &BigUint, rounds: usize) -> Vec<BigUint> {
+    let mut state = n.iter_u64_digits().take(2).fold(0, |acc: u64, d| acc ^ d);
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    // Reduce each draw to a base from 2 to n - 2, since n may be as small as 2^64 + 1.
+    let range = n - 3u32;
+    core::iter::once(BigUint::from(2u32))
+        .chain((1..rounds).map(|_| BigUint::from(next()) % &range + 2u32))
+        .collect()
+}
+
+/// Returns whether `n` is a probable prime after trial division and `rounds` Miller–Rabin rounds,
+/// at least one.
+///
+/// Every number below 2^64 gets an exact answer. Above that, the first round is to base 2, and
+/// the rest run as separate tasks on the executor for large enough `n`.
+pub(super) fn is_probably_prime(n: &BigUint, rounds: usize) -> bool {
+    match trial_division(n) {
+        Trial::Prime => return true,
+        Trial::Composite => return false,
+        Trial::Unknown => {}
+    }
+    if let Some(n) = n.to_u64() {
+        return is_prime_u64(n);
+    }
+
+    let mr = MillerRabin::new(n);
+    let bases = bases(n, rounds.max(1));
+    // Base 2 alone rules out nearly all composites, so it goes first on its own.
+    if !mr.test(&bases[0]) {
+        return false;
+    }
+    let rest = &bases[1..];
+    if rest.len() > 1
+        && n.bits() >= PARALLEL_ROUNDS_BITS
+        && executor::executor().current_num_threads() > 1
+    {
+        let mut results: Vec<_> = rest.iter().map(|base| (base, false)).collect();
+        executor::for_each(&mut results, |(base, passed)| *passed = mr.test(base));
+        let passed = results.iter().all(|&(_, passed)| passed);
+        executor::verify(
+            "Miller-Rabin",
+            &passed,
+            || rest.iter().all(|base| mr.test(base)),
+            n,
+        );
+        passed
+    } else {
+        rest.iter().all(|base| mr.test(base))
+    }
+}
+
+/// Returns the Jacobi symbol (a / n) for odd `n`.
+fn jacobi(mut a: u64, mut n: u64) -> i32 {
+    debug_assert!(n.is_odd());
+    let mut result = 1;
+    a %= n;
+    while a != 0 {
+        while a.is_even() {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                result = -result;
+            }
+        }
+        core::mem::swap(&mut a, &mut n);
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        a %= n;
+    }
+    if n == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+/// Returns the Jacobi symbol (d / n) for a small `d` and an odd `n` too large for a `u64`.
+fn jacobi_big(d: i64, n: &BigUint) -> i32 {
+    let low = n.iter_u64_digits().next().unwrap();
+    let abs = d.unsigned_abs();
+    // (-1 / n) is -1 exactly when n = 3 (mod 4).
+    let mut result = if d < 0 && low % 4 == 3 { -1 } else { 1 };
+    // Strip twos from |d|, each contributing (2 / n).
+    let twos = abs.trailing_zeros();
+    if twos % 2 == 1 && (low % 8 == 3 || low % 8 == 5) {
+        result = -result;
+    }
+    let odd = abs >> twos;
+    // Quadratic reciprocity, with (n / odd) = (n mod odd / odd).
+    if odd % 4 == 3 && low % 4 == 3 {
+        result = -result;
+    }
+    result * jacobi(DigitDivisor::new(odd).rem(n), odd)
+}
+
+/// Returns `x / 2 (mod n)` for `x < n`.
+#[inline]
+fn half_mod(x: BigUint, n: &BigUint) -> BigUint {
+    if x.is_odd() {
+        (x + n) >> 1
+    } else {
+        x >> 1
+    }
+}
+
+/// Returns `a - b (mod n)` for `a, b < n`.
+#[inline]
+fn sub_mod(a: BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= *b {
+        a - b
+    } else {
+        a + n - b
+    }
+}
+
+/// The strong Lucas probable-prime test, with Selfridge's parameters.
+///
+/// D is the first of 5, -7, 9, -11, ... with (D / n) = -1, P = 1 and Q = (1 - D) / 4. With
+/// n + 1 = d * 2^s, n passes if U_d = 0, or V_(d * 2^r) = 0 for some r < s, all modulo n.
+fn strong_lucas(n: &BigUint) -> bool {
+    let mut d = 5i64;
+    loop {
+        match jacobi_big(d, n) {
+            -1 => break,
+            // n has no factor below the trial division bound, so it isn't |d|.
+            0 => return false,
+            _ => {}
+        }
+        // A square never has (D / n) = -1, so check for one before searching much further.
+        if d == 13 && is_square(n) {
+            return false;
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+
+    let to_mod = |x: i64| -> BigUint {
+        let x_abs = BigUint::from(x.unsigned_abs());
+        if x < 0 {
+            n - x_abs
+        } else {
+            x_abs
+        }
+    };
+    let big_d = to_mod(d);
+    let q = to_mod((1 - d) / 4);
+
+    let n_plus_1 = n + 1u32;
+    let s = n_plus_1.trailing_zeros().unwrap();
+    let k = &n_plus_1 >> s;
+
+    // U_1 = 1, V_1 = P = 1, and Q^1, walking the bits of k from the top.
+    let mut u = BigUint::one();
+    let mut v = BigUint::one();
+    let mut qk = q.clone();
+    for bit in k.iter_bits().rev().skip(1) {
+        // U_2j = U_j V_j, V_2j = V_j^2 - 2 Q^j
+        u = (&u * &v) % n;
+        v = sub_mod(v.square() % n, &((&qk << 1u8) % n), n);
+        qk = qk.square() % n;
+        if bit {
+            // U_(j+1) = (P U_j + V_j) / 2, V_(j+1) = (D U_j + P V_j) / 2
+            let next_u = half_mod((&u + &v) % n, n);
+            v = half_mod((&big_d * &u + &v) % n, n);
+            u = next_u;
+            qk = (&qk * &q) % n;
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        // V_2j = V_j^2 - 2 Q^j
+        v = sub_mod(v.square() % n, &((&qk << 1u8) % n), n);
+        if v.is_zero() {
+            return true;
+        }
+        qk = qk.square() % n;
+    }
+    false
+}
+
+/// Returns whether `n` passes the Baillie–PSW test: trial division, a strong probable-prime test
+/// to base 2 and a strong Lucas test. No composite is known to pass, and none below 2^64 does.
+pub(super) fn is_prime_bpsw(n: &BigUint) -> bool {
+    match trial_division(n) {
+        Trial::Prime => return true,
+        Trial::Composite => return false,
+        Trial::Unknown => {}
+    }
+    if let Some(n) = n.to_u64() {
+        return is_prime_u64(n);
+    }
+    MillerRabin::new(n).test(&BigUint::from(2u32)) && strong_lucas(n)
+}
+
+/// Returns the wheel sieve for a range that ends at `end`, if it does.
+fn sieve_for(end: Option<&BigUint>) -> WheelSieve {
+    let bound = match end.and_then(|end| end.sqrt().to_u64()) {
+        Some(root) => root.min(SIEVE_BOUND),
+        None => SIEVE_BOUND,
+    };
+    WheelSieve::new(bound)
+}
+
+/// Returns how many `WHEEL`s a segment near `n` spans, about two prime gaps on average.
+fn segment_wheels(n: &BigUint) -> usize {
+    (n.bits() / 4 + 16) as usize
+}
+
+/// Returns how many segments, or candidates, near `n` to take on at once.
+fn batch_size(n: &BigUint) -> usize {
+    if n.bits() >= PARALLEL_SIEVE_BITS {
+        executor::executor().current_num_threads().max(1)
+    } else {
+        1
+    }
+}
+
+/// Sieves the segments, given as a start and a number of wheels, and returns the survivors of
+/// each in turn.
+fn sieve_segments(sieve: &WheelSieve, segments: &[(BigUint, usize)]) -> Vec<BigUint> {
+    if segments.len() > 1 {
+        let mut results: Vec<_> = segments
+            .iter()
+            .map(|segment| (segment, Vec::new()))
+            .collect();
+        executor::for_each(&mut results, |((base, wheels), survivors)| {
+            *survivors = sieve.segment(base, *wheels)
+        });
+        let survivors: Vec<BigUint> = results.into_iter().flat_map(|(_, s)| s).collect();
+        executor::verify(
+            "segment sieve",
+            &survivors,
+            || {
+                segments
+                    .iter()
+                    .flat_map(|(base, wheels)| sieve.segment(base, *wheels))
+                    .collect()
+            },
+            segments,
+        );
+        survivors
+    } else {
+        segments
+            .iter()
+            .flat_map(|(base, wheels)| sieve.segment(base, *wheels))
+            .collect()
+    }
+}
+
+/// Returns whether each of the sieved candidates passes the Baillie–PSW test.
+fn test_candidates(candidates: &[BigUint]) -> Vec<bool> {
+    if candidates.len() > 1 {
+        let mut results: Vec<_> = candidates.iter().map(|n| (n, false)).collect();
+        executor::for_each(&mut results, |(n, passed)| *passed = is_prime_bpsw(n));
+        let passed: Vec<bool> = results.into_iter().map(|(_, passed)| passed).collect();
+        executor::verify(
+            "prime candidates",
+            &passed,
+            || candidates.iter().map(is_prime_bpsw).collect(),
+            candidates,
+        );
+        passed
+    } else {
+        candidates.iter().map(is_prime_bpsw).collect()
+    }
+}
+
+/// An iterator over the primes in a range of [`BigUint`]s, in increasing order.
+///
+/// This is created by [`PrimeIter::range`]. The range is sieved a segment at a time, with a
+/// wheel of 30 and the primes up to 2^16, and the survivors are then tested with
+/// [`BigUint::is_prime_bpsw`]. For large numbers, several segments are sieved and several
+/// candidates tested at once, as separate tasks on the current [`executor`](crate::executor).
+#[derive(Clone, Debug)]
+pub struct PrimeIter {
+    sieve: WheelSieve,
+    start: BigUint,
+    end: Option<BigUint>,
+    /// The start of the next segment to sieve, a multiple of `WHEEL`.
+    next_base: BigUint,
+    wheels: usize,
+    /// Survivors of the sieve in the range, not yet tested.
+    candidates: VecDeque<BigUint>,
+    /// Candidates that passed the test, not yet returned.
+    primes: VecDeque<BigUint>,
+}
+
+impl PrimeIter {
+    /// Returns an iterator over the primes `p` with `start <= p < end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::{BigUint, PrimeIter};
+    ///
+    /// let start = BigUint::from(1u32) << 64u8;
+    /// let end = &start + 100u32;
+    /// let primes: Vec<BigUint> = PrimeIter::range(start.clone(), end).collect();
+    /// let gaps: Vec<BigUint> = primes.iter().map(|p| p - &start).collect();
+    /// assert_eq!(gaps, [13u32, 37, 51, 81, 93].map(BigUint::from));
+    /// ```
+    pub fn range(start: BigUint, end: BigUint) -> Self {
+        PrimeIter::new(start, Some(end))
+    }
+
+    fn new(start: BigUint, end: Option<BigUint>) -> Self {
+        let next_base = &start - &start % WHEEL;
+        let mut wheels = segment_wheels(end.as_ref().unwrap_or(&start));
+        if let Some(end) = &end {
+            // Don't sieve far past the end of a short range.
+            if let Some(len) = end.checked_sub(&next_base).and_then(|len| len.to_usize()) {
+                wheels = wheels.min(len.div_ceil(WHEEL as usize)).max(1);
+            }
+        }
+        PrimeIter {
+            sieve: sieve_for(end.as_ref()),
+            start,
+            end,
+            next_base,
+            wheels,
+            candidates: VecDeque::new(),
+            primes: VecDeque::new(),
+        }
+    }
+
+    /// Returns whether `n` is before the end of the range.
+    fn before_end(&self, n: &BigUint) -> bool {
+        !matches!(&self.end, Some(end) if n >= end)
+    }
+
+    /// Sieves the next batch of segments into the candidates, or returns `false` if the range
+    /// has no more segments.
+    fn sieve_next(&mut self) -> bool {
+        let span = WHEEL * self.wheels as u64;
+        let mut segments = Vec::new();
+        while segments.len() < batch_size(&self.next_base) && self.before_end(&self.next_base) {
+            segments.push((self.next_base.clone(), self.wheels));
+            self.next_base += span;
+        }
+        if segments.is_empty() {
+            return false;
+        }
+        for n in sieve_segments(&self.sieve, &segments) {
+            if n >= self.start && self.before_end(&n) {
+                self.candidates.push_back(n);
+            }
+        }
+        true
+    }
+}
+
+impl Iterator for PrimeIter {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        loop {
+            if let Some(p) = self.primes.pop_front() {
+                return Some(p);
+            }
+            if self.candidates.is_empty() {
+                if !self.sieve_next() {
+                    return None;
+                }
+                continue;
+            }
+            let len = batch_size(&self.candidates[0]).min(self.candidates.len());
+            let batch: Vec<BigUint> = self.candidates.drain(..len).collect();
+            let passed = test_candidates(&batch);
+            for (n, passed) in batch.into_iter().zip(passed) {
+                if passed {
+                    self.primes.push_back(n);
+                }
+            }
+        }
+    }
+}
+
+impl FusedIterator for PrimeIter {}
+
+/// Returns the smallest prime `p >= n`.
+pub(super) fn next_prime(n: &BigUint) -> BigUint {
+    PrimeIter::new(n.clone(), None).next().unwrap()
+}
+
+/// Returns the largest prime `p <= n`, if there is one.
+///
+/// This sieves segments going down from `n`, and tests their candidates from the top.
+pub(super) fn prev_prime(n: &BigUint) -> Option<BigUint> {
+    let end = n + 1u32;
+    let sieve = sieve_for(Some(&end));
+    let span = BigUint::from(WHEEL * segment_wheels(n) as u64);
+    let batch = batch_size(n);
+
+    // the end of the next segment down, a multiple of `WHEEL`
+    let mut top = (&end + (WHEEL - 1)) / WHEEL * WHEEL;
+    while !top.is_zero() {
+        let mut segments = Vec::new();
+        while segments.len() < batch && !top.is_zero() {
+            let base = top.checked_sub(&span).unwrap_or_default();
+            let wheels = ((&top - &base) / WHEEL).to_usize().unwrap();
+            segments.push((base.clone(), wheels));
+            top = base;
+        }
+        // Sieve the segments in increasing order, and test the survivors in decreasing order.
+        segments.reverse();
+        let mut candidates = sieve_segments(&sieve, &segments);
+        candidates.retain(|c| *c < end);
+        candidates.reverse();
+        for chunk in candidates.chunks(batch) {
+            let passed = test_candidates(chunk);
+            if let Some(i) = passed.iter().position(|&passed| passed) {
+                return Some(chunk[i].clone());
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_is_prime() {
+    use super::sieve::primes_up_to;
+
+    assert_eq!(SMALL_PRIMES[..], primes_up_to(TRIAL_DIVISION_BOUND)[..]);
+
+    let primes = primes_up_to(20_000);
+    for n in 0..20_000u32 {
+        let expected = primes.binary_search(&u64::from(n)).is_ok();
+        let n = BigUint::from(n);
+        assert_eq!(is_probably_prime(&n, 2), expected, "{}", n);
+        assert_eq!(is_prime_bpsw(&n), expected, "{}", n);
+    }
+
+    // strong pseudoprimes to several bases, and Carmichael numbers
+    for n in [
+        3_215_031_751u64,
+        3_825_123_056_546_413_051,
+        561,
+        1_729,
+        41_041,
+    ] {
+        assert!(!is_probably_prime(&BigUint::from(n), 1));
+        assert!(!is_prime_bpsw(&BigUint::from(n)));
+    }
+    assert!(is_probably_prime(&BigUint::from(u64::MAX - 58), 1));
+
+    // just above 2^64, where the bases must still be below n - 1
+    let f = (BigUint::one() << 64u8) + 1u32; // 274177 * 67280421310721
+    for n in [&f, &(&f + 2u32)] {
+        assert!(bases(n, 50)
+            .iter()
+            .all(|b| *b >= BigUint::from(2u32) && *b < n - 1u32));
+    }
+    assert!(!is_probably_prime(&f, 10));
+    assert!(!is_prime_bpsw(&f));
+
+    let mersenne = |p: u32| (BigUint::one() << p) - 1u32;
+    for p in [89, 107, 127, 521, 607] {
+        assert!(is_probably_prime(&mersenne(p), 8), "{}", p);
+        assert!(is_prime_bpsw(&mersenne(p)), "{}", p);
+    }
+    for p in [83, 101, 131, 523] {
+        assert!(!is_probably_prime(&mersenne(p), 8), "{}", p);
+        assert!(!is_prime_bpsw(&mersenne(p)), "{}", p);
+    }
+
+    // a product of two primes above the trial division bound, and a prime square
+    let a = mersenne(89);
+    let b = mersenne(61);
+    assert!(!is_probably_prime(&(&a * &b), 4));
+    assert!(!is_prime_bpsw(&(&a * &b)));
+    assert!(!is_prime_bpsw(&(&a * &a)));
+    assert!(!is_prime_bpsw(&BigUint::from(1_000_003u64 * 1_000_003)));
+
+    // a strong pseudoprime to all prime bases up to 37 (Arnault), caught by the Lucas test
+    let arnault = BigUint::parse_bytes(b"3317044064679887385961981", 10).unwrap();
+    assert!(!is_prime_bpsw(&arnault));
+    assert!(!is_probably_prime(&arnault, 8));
+}
+
+#[test]
+fn test_prime_iter() {
+    use super::sieve::primes_up_to;
+
+    let primes = primes_up_to(20_000);
+    for (start, end) in [
+        (0u64, 20_000u64),
+        (0, 2),
+        (0, 3),
+        (2, 3),
+        (7, 8),
+        (100, 100),
+        (9_990, 10_010),
+    ] {
+        let expected: Vec<BigUint> = primes
+            .iter()
+            .filter(|&&p| p >= start && p < end)
+            .map(|&p| BigUint::from(p))
+            .collect();
+        let got: Vec<BigUint> =
+            PrimeIter::range(BigUint::from(start), BigUint::from(end)).collect();
+        assert_eq!(got, expected, "{}..{}", start, end);
+    }
+    assert_eq!(
+        PrimeIter::range(BigUint::from(10u32), BigUint::from(5u32)).next(),
+        None
+    );
+
+    for n in [0u64, 1, 2, 3, 4, 10, 29, 30, 31, 9_999, 19_000] {
+        let i = primes.partition_point(|&p| p < n);
+        assert_eq!(
+            next_prime(&BigUint::from(n)),
+            BigUint::from(primes[i]),
+            "{}",
+            n
+        );
+        let i = primes.partition_point(|&p| p <= n);
+        let expected = i.checked_sub(1).map(|i| BigUint::from(primes[i]));
+        assert_eq!(prev_prime(&BigUint::from(n)), expected, "{}", n);
+    }
+
+    // the primes nearest 2^64 and 2^128
+    let x = BigUint::one() << 64u8;
+    assert_eq!(next_prime(&x), &x + 13u32);
+    assert_eq!(prev_prime(&x), Some(&x - 59u32));
+    assert_eq!(prev_prime(&(&x - 59u32)), Some(&x - 59u32));
+    let x = BigUint::one() << 128u8;
+    assert_eq!(next_prime(&x), &x + 51u32);
+    assert_eq!(prev_prime(&x), Some(&x - 159u32));
+    let m127 = (BigUint::one() << 127u8) - 1u32;
+    assert_eq!(next_prime(&m127), m127);
+
+    // a range across segments, checked against testing every number
+    let start = (BigUint::one() << 100u8) - 2000u32;
+    let end = &start + 4000u32;
+    let expected: Vec<BigUint> = (0..4000u32)
+        .map(|i| &start + i)
+        .filter(is_prime_bpsw)
+        .collect();
+    let got: Vec<BigUint> = PrimeIter::range(start, end).collect();
+    assert_eq!(got, expected);
+}
@@ -0,0 +1,296 @@
+//! Generation of random primes.
+#![cfg(feature = "rand")]
+#![cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+
+use super::division::rem_digit;
+use super::prime::SMALL_PRIMES;
+use super::BigUint;
+
+use crate::big_digit::BigDigit;
+use crate::executor;
+
+use alloc::vec::Vec;
+use num_integer::Integer;
+use num_traits::One;
+use rand::RngCore;
+
+/// Candidates are drawn and tested in batches from this size.
+const PARALLEL_SEARCH_BITS: u64 = 128;
+
+/// How many candidates make a batch. This is fixed rather than the executor's thread count, so
+/// that the draws from the generator don't depend on the machine.
+const SEARCH_BATCH: usize = 8;
+
+/// A trait for generating random primes.
+///
+/// The `rand` feature must be enabled to use this. See crate-level documentation for details.
+///
+/// Each method draws candidates of the given size from the generator, and tests them with
+/// [`BigUint::is_prime_bpsw`]. For large sizes, candidates are drawn in batches of a fixed size,
+/// and each batch is tested on the current [`executor`](crate::executor), one task per candidate
+/// when it has more than one thread. The first prime of the batch in drawing order is returned.
+/// Both the primes and the number of draws depend only on the generator, not on the executor, so
+/// a seeded generator gives the same sequence of primes on every machine.
+///
+/// # Examples
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// use rust_monty_parallel::{BigUint, RandPrime};
+///
+/// let mut rng = ChaCha8Rng::seed_from_u64(1);
+/// let p = rng.gen_safe_prime(64);
+/// assert_eq!(p.bits(), 64);
+/// assert!(p.is_prime_bpsw() && (p >> 1u8).is_prime_bpsw());
+///
+/// let q = rng.gen_rsa_prime(256, &BigUint::from(65537u32));
+/// assert_eq!(q.bits(), 256);
+/// ```
+pub trait RandPrime {
+    /// Generates a random odd prime of exactly `bits` bits. Panics if `bits` is less than 2.
+    fn gen_prime(&mut self, bits: u64) -> BigUint;
+
+    /// Generates a random safe prime `p` of exactly `bits` bits, one where `(p - 1) / 2` is also
+    /// prime, as for a Diffie–Hellman group. Panics if `bits` is less than 3.
+    fn gen_safe_prime(&mut self, bits: u64) -> BigUint;
+
+    /// Generates a random prime `p` of exactly `bits` bits with `p = a (mod m)`.
+    ///
+    /// Panics if `a` and `m` have a common factor, or if `m` is zero or too large for the size,
+    /// which it is if `m`, or `2 * m` for an odd `m`, has `bits - 1` or more bits.
+    fn gen_prime_congruent(&mut self, bits: u64, a: &BigUint, m: &BigUint) -> BigUint;
+
+    /// Generates a random prime `p` of exactly `bits` bits to be a factor of an RSA modulus with
+    /// the public exponent `e`, as in FIPS 186-4, appendix B.3.3.
+    ///
+    /// That is, `p >= sqrt(2) * 2^(bits - 1)`, so that the product of two such primes has exactly
+    /// `2 * bits` bits, and `gcd(p - 1, e) = 1`. Panics if `bits` is less than 2, or if `e` is
+    /// even or less than 3.
+    fn gen_rsa_prime(&mut self, bits: u64, e: &BigUint) -> BigUint;
+}
+
+/// Returns a random number below `2^bits`, from 32 bits of the generator at a time.
+fn random_bits<R: RngCore + ?Sized>(rng: &mut R, bits: u64) -> BigUint {
+    let len = Integer::div_ceil(&bits, &32) as usize;
+    let mut data: Vec<u32> = (0..len).map(|_| rng.next_u32()).collect();
+    let rem = bits % 32;
+    if rem > 0 {
+        data[len - 1] >>= 32 - rem;
+    }
+    BigUint::new(data)
+}
+
+/// Returns a random odd number of exactly `bits` bits.
+fn random_odd<R: RngCore + ?Sized>(rng: &mut R, bits: u64) -> BigUint {
+    let mut n = random_bits(rng, bits);
+    n.set_bit(bits - 1, true);
+    n.set_bit(0, true);
+    n
+}
+
+/// Draws candidates until one passes `test`, and returns it.
+///
+/// `candidate` may reject a draw with `None`. Candidates of `bits` bits from
+/// `PARALLEL_SEARCH_BITS` are drawn `SEARCH_BATCH` at a time, and tested as one task each if the
+/// executor has more than one thread, or in order until the first prime otherwise.
+fn search<R, C, T>(rng: &mut R, bits: u64, mut candidate: C, test: T) -> BigUint
+where
+    R: RngCore + ?Sized,
+    C: FnMut(&mut R) -> Option<BigUint>,
+    T: Fn(&BigUint) -> bool + Sync,
+{
+    let batch = if bits >= PARALLEL_SEARCH_BITS {
+        SEARCH_BATCH
+    } else {
+        1
+    };
+    loop {
+        let mut candidates = Vec::with_capacity(batch);
+        while candidates.len() < batch {
+            candidates.extend(candidate(rng));
+        }
+        let first = if batch > 1 && executor::executor().current_num_threads() > 1 {
+            let mut results: Vec<_> = candidates.iter().map(|n| (n, false)).collect();
+            executor::for_each(&mut results, |(n, passed)| *passed = test(n));
+            let first = results.iter().position(|&(_, passed)| passed);
+            executor::verify(
+                "prime search",
+                &first,
+                || candidates.iter().position(&test),
+                &candidates,
+            );
+            first
+        } else {
+            candidates.iter().position(&test)
+        };
+        if let Some(i) = first {
+            return candidates.swap_remove(i);
+        }
+    }
+}
+
+impl<R: RngCore + ?Sized> RandPrime for R {
+    fn gen_prime(&mut self, bits: u64) -> BigUint {
+        assert!(bits >= 2, "a prime has at least 2 bits");
+        search(
+            self,
+            bits,
+            |rng| Some(random_odd(rng, bits)),
+            BigUint::is_prime_bpsw,
+        )
+    }
+
+    fn gen_safe_prime(&mut self, bits: u64) -> BigUint {
+        assert!(bits >= 3, "a safe prime has at least 3 bits");
+        // Neither q nor 2q + 1 may have an odd factor below 1000, so q mod r is neither 0 nor
+        // (r - 1) / 2.
+        let sieve = |q: &BigUint| {
+            q.bits() <= 10
+                || SMALL_PRIMES[1..].iter().all(|&r| {
+                    let rem = rem_digit(q, r as BigDigit);
+                    rem != 0 && rem != ((r - 1) / 2) as BigDigit
+                })
+        };
+        search(
+            self,
+            bits,
+            |rng| {
+                let q = random_odd(rng, bits - 1);
+                sieve(&q).then(|| (q << 1u8) + 1u32)
+            },
+            |p| p.is_prime_bpsw() && (p >> 1u8).is_prime_bpsw(),
+        )
+    }
+
+    fn gen_prime_congruent(&mut self, bits: u64, a: &BigUint, m: &BigUint) -> BigUint {
+        assert!(bits >= 2, "a prime has at least 2 bits");
+        assert!(m.bits() > 0, "the modulus must not be zero");
+        // Fold the parity into the congruence, so every candidate is odd.
+        let (modulus, residue) = if m.is_even() {
+            (m.clone(), a % m)
+        } else {
+            let r = a % m;
+            let r = if r.is_odd() { r } else { r + m };
+            (m << 1u8, r)
+        };
+        assert!(
+            modulus.bits() < bits,
+            "the modulus is too large for primes of {} bits",
+            bits
+        );
+        assert!(
+            residue.gcd(&modulus).is_one(),
+            "the residue and modulus have a common factor"
+        );
+
+        let low = BigUint::one() << (bits - 1);
+        search(
+            self,
+            bits,
+            |rng| {
+                let x = random_bits(rng, bits) | &low;
+                let mut p = &x - &x % &modulus + &residue;
+                if p < low {
+                    p += &modulus;
+                }
+                (p.bits() == bits).then_some(p)
+            },
+            BigUint::is_prime_bpsw,
+        )
+    }
+
+    fn gen_rsa_prime(&mut self, bits: u64, e: &BigUint) -> BigUint {
+        assert!(bits >= 2, "a prime has at least 2 bits");
+        assert!(
+            e.is_odd() && e.bits() >= 2,
+            "the public exponent must be odd and at least 3"
+        );
+        // floor(sqrt(2) * 2^(bits - 1)), which p must be above
+        let min = (BigUint::one() << (2 * bits - 1)).sqrt();
+        search(
+            self,
+            bits,
+            |rng| {
+                let p = random_odd(rng, bits);
+                (p > min && (&p - 1u32).gcd(e).is_one()).then_some(p)
+            },
+            BigUint::is_prime_bpsw,
+        )
+    }
+}
+
+#[cfg(test)]
+struct SplitMix64(u64);
+
+#[cfg(test)]
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_gen_prime() {
+    let mut rng = SplitMix64(1);
+    for bits in [2, 3, 8, 31, 32, 33, 64, 65, 200] {
+        let p = rng.gen_prime(bits);
+        assert_eq!(p.bits(), bits);
+        assert!(p.is_odd() && p.is_prime_bpsw(), "{}", p);
+    }
+    assert_eq!(
+        SplitMix64(7).gen_prime(160),
+        SplitMix64(7).gen_prime(160),
+        "the same generator state gives the same prime"
+    );
+
+    for bits in [3, 4, 5, 16, 64, 130] {
+        let p = rng.gen_safe_prime(bits);
+        assert_eq!(p.bits(), bits);
+        assert!(p.is_prime_bpsw() && (&p >> 1u8).is_prime_bpsw(), "{}", p);
+    }
+
+    for (bits, a, m) in [
+        (100, 3u64, 4u64),
+        (100, 1, 1_000_003),
+        (100, 12_345, 1 << 20),
+        (8, 1, 1),
+        (130, 2, 3),
+    ] {
+        let (a, m) = (BigUint::from(a), BigUint::from(m));
+        let p = rng.gen_prime_congruent(bits, &a, &m);
+        assert_eq!(p.bits(), bits);
+        assert_eq!(&p % &m, &a % &m);
+        assert!(p.is_prime_bpsw(), "{}", p);
+    }
+
+    let min = (BigUint::one() << 255u8).sqrt();
+    for e in [3u32, 65_537] {
+        let e = BigUint::from(e);
+        let p = rng.gen_rsa_prime(128, &e);
+        assert_eq!(p.bits(), 128);
+        assert!(p > min);
+        assert!((&p - 1u32).gcd(&e).is_one());
+        assert!(p.is_prime_bpsw(), "{}", p);
+    }
+}
@@ -0,0 +1,301 @@
+use super::division::{digits, shl_digits};
+use super::sieve::primes_up_to;
+use super::{BigUint, DigitDivisor};
+
+use crate::big_digit::{self, DoubleBigDigit};
+
+use num_integer::{Integer, Roots};
+use num_traits::{One, Pow, ToPrimitive, Zero};
+
+/// Returns `(s, r)` with `s` the truncated square root of `x` and `r = x - s * s`.
+///
+/// `x` is first shifted left by an even number of bits, to an even number of digits whose top
+/// one has one of its two high bits set, as `sqrt_rem_normalized` requires. The root of that
+/// is the root of `x` shifted by half as many bits, with a few more low bits to drop.
+pub(super) fn sqrt_rem(x: &BigUint) -> (BigUint, BigUint) {
+    if x.is_zero() {
+        return (BigUint::ZERO, BigUint::ZERO);
+    }
+    let n = x.data.len().div_ceil(2);
+    let shift = (2 * n as u64 * u64::from(big_digit::BITS) - x.bits()) / 2;
+    if shift == 0 {
+        return sqrt_rem_normalized(x, n);
+    }
+    let (s, _) = sqrt_rem_normalized(&(x << (2 * shift)), n);
+    let s = s >> shift;
+    let r = x - s.square();
+    (s, r)
+}
+
+/// Karatsuba square root, by Zimmermann, "Karatsuba Square Root" (1999), as in Brent &
+/// Zimmermann, Modern Computer Arithmetic, Algorithm 1.12.
+///
+/// `a` must have 2n digits, with one of the two high bits of the top digit set. Writing
+/// a = a' * B^2k + a1 * B^k + a0 with k = n / 2, the root s' of the top half a' comes from a
+/// recursive call, and dividing the remainder and the next k digits by 2s' gives the next k
+/// digits of the root as in Newton's iteration. The result is at most one too large, which the
+/// sign of the remainder reveals. So the cost is a few multiplications and divisions of half the
+/// size, with no iteration to convergence.
+fn sqrt_rem_normalized(a: &BigUint, n: usize) -> (BigUint, BigUint) {
+    debug_assert_eq!(a.data.len(), 2 * n);
+    debug_assert!(a.data[2 * n - 1] >> (big_digit::BITS - 2) != 0);
+
+    if n == 1 {
+        let a = big_digit::to_doublebigdigit(a.data[1], a.data[0]);
+        let s: DoubleBigDigit = a.sqrt();
+        return (BigUint::from(s), BigUint::from(a - s * s));
+    }
+
+    let k = n / 2;
+    let (s, r) = sqrt_rem_normalized(&digits(a, 2 * k, 2 * n), n - k);
+    let (q, u) = (shl_digits(r, k) + digits(a, k, 2 * k)).div_rem(&(&s << 1u8));
+    let s = shl_digits(s, k) + &q;
+    let r = shl_digits(u, k) + digits(a, 0, k);
+    let q2 = q.square();
+    if r >= q2 {
+        (s, r - q2)
+    } else {
+        let r = r + (&s << 1u8) - q2 - 1u32;
+        (s - 1u32, r)
+    }
+}
+
+/// Returns the truncated `k`th root of `x`, for `k >= 2`.
+///
+/// Newton's iteration doubles the number of correct bits with each step, so rather than iterating
+/// at full precision from a rough guess, this finds the root of `x` without the low `k * h`
+/// bits, where h is half the number of bits of the root. Scaled back up, that is an
+/// overestimate of the root with its high half correct, from which one or two steps at full
+/// precision reach the root.
+pub(super) fn nth_root(x: &BigUint, k: u32) -> BigUint {
+    debug_assert!(k >= 2);
+    if k == 2 {
+        return x.sqrt();
+    }
+    let bits = x.bits();
+    if bits <= u64::from(k) {
+        // The root of non-zero values less than 2^k can only be 1.
+        return if x.is_zero() {
+            BigUint::ZERO
+        } else {
+            BigUint::one()
+        };
+    }
+    if let Some(x) = x.to_u64() {
+        return x.nth_root(k).into();
+    }
+
+    let low = Integer::div_ceil(&bits, &u64::from(k)) / 2;
+    let high = nth_root(&(x >> (low * u64::from(k))), k);
+    let mut root = (high + 1u32) << low;
+
+    // From above, Newton's iteration decreases until it reaches the truncated root.
+    loop {
+        let next = (&root * (k - 1) + x / Pow::pow(&root, k - 1)) / k;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// Returns a bit mask of the squares modulo `m`, for `m <= 128`.
+const fn squares_mod(m: u32) -> u128 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < m {
+        mask |= 1 << (i * i % m);
+        i += 1;
+    }
+    mask
+}
+
+const SQUARES_MOD_64: u128 = squares_mod(64);
+const SQUARES_MOD_63: u128 = squares_mod(63);
+const SQUARES_MOD_65: u128 = squares_mod(65);
+const SQUARES_MOD_11: u128 = squares_mod(11);
+
+/// Returns whether `x` is a perfect square.
+pub(super) fn is_square(x: &BigUint) -> bool {
+    may_be_square(x) && sqrt_rem(x).1.is_zero()
+}
+
+/// Returns false if `x` is not a quadratic residue modulo 64, 63, 65 or 11, and so not a square.
+///
+/// Only about one in 120 non-squares passes these tests, which take one pass over the digits,
+/// so they are worth running before a square root.
+fn may_be_square(x: &BigUint) -> bool {
+    let low = x.data.first().map_or(0, |&d| d % 64);
+    if (SQUARES_MOD_64 >> low) & 1 == 0 {
+        return false;
+    }
+    let r = DigitDivisor::new(63 * 65 * 11).rem(x);
+    if (SQUARES_MOD_63 >> (r % 63)) & 1 == 0
+        || (SQUARES_MOD_65 >> (r % 65)) & 1 == 0
+        || (SQUARES_MOD_11 >> (r % 11)) & 1 == 0
+    {
+        return false;
+    }
+    true
+}
+
+/// Returns whether the small `n` is prime, by trial division.
+fn is_small_prime(n: u64) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !Integer::is_multiple_of(&n, &d))
+}
+
+/// Returns `b^e mod m` for `m < 2^32`.
+fn pow_mod(mut b: u64, mut e: u64, m: u64) -> u64 {
+    let mut r = 1 % m;
+    b %= m;
+    while e > 0 {
+        if e & 1 == 1 {
+            r = r * b % m;
+        }
+        b = b * b % m;
+        e >>= 1;
+    }
+    r
+}
+
+/// Returns the `p`th root of `x` if it is exact, for a prime `p`.
+///
+/// An exact `p`th power modulo a prime q = 1 (mod p) is zero or one of only (q - 1) / p `p`th
+/// power residues, which the residue tests check for two such q before the root is computed.
+fn exact_root(x: &BigUint, p: u32) -> Option<BigUint> {
+    if p == 2 {
+        if !may_be_square(x) {
+            return None;
+        }
+        let (root, rem) = sqrt_rem(x);
+        return rem.is_zero().then_some(root);
+    }
+
+    let p64 = u64::from(p);
+    let mut moduli = (1..)
+        .map(|i| 2 * i * p64 + 1)
+        .take_while(|&q| q < 1 << 32)
+        .filter(|&q| is_small_prime(q))
+        .take(2);
+    if let (Some(q1), Some(q2)) = (moduli.next(), moduli.next()) {
+        let r = DigitDivisor::new(q1 * q2).rem(x);
+        for q in [q1, q2] {
+            let r = r % q;
+            if r != 0 && pow_mod(r, (q - 1) / p64, q) != 1 {
+                return None;
+            }
+        }
+    }
+
+    let root = nth_root(x, p);
+    if Pow::pow(&root, p) == *x {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// Returns `(a, k)` with `x = a^k` for the largest such `k >= 2`, if there is one.
+///
+/// If x = a^k with a not a perfect power itself, x is a `p`th power exactly for the primes p
+/// dividing k. So this takes `p`th roots for increasing primes p, each as often as possible,
+/// while the power of two dividing x rules out any p that doesn't divide its exponent.
+pub(super) fn perfect_power(x: &BigUint) -> Option<(BigUint, u32)> {
+    if x.is_zero() || x.is_one() {
+        return Some((x.clone(), 2));
+    }
+
+    let mut base = x.clone();
+    let mut exp = 1;
+    let mut twos = x.trailing_zeros().unwrap();
+    let primes = primes_up_to(x.bits());
+    let mut i = 0;
+    // A `p`th power of at least 2 has more than p bits.
+    while i < primes.len() && primes[i] < base.bits() {
+        let p = primes[i] as u32;
+        if Integer::is_multiple_of(&twos, &u64::from(p)) {
+            if let Some(root) = exact_root(&base, p) {
+                base = root;
+                exp *= p;
+                twos /= u64::from(p);
+                continue;
+            }
+        }
+        i += 1;
+    }
+    if exp > 1 {
+        Some((base, exp))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_sqrt_rem() {
+    use super::{biguint_from_vec, TestRng};
+
+    let mut rng = TestRng::new(0x9e37_79b9_7f4a_7c15);
+
+    for len in [1, 2, 3, 4, 5, 7, 8, 16, 33, 100, 257] {
+        let x = biguint_from_vec(rng.digits(len));
+        for x in [
+            x.clone(),
+            &x >> 3u8,
+            x.square(),
+            x.square() - 1u32,
+            x.square() + 1u32,
+        ] {
+            let (s, r) = sqrt_rem(&x);
+            assert_eq!(&s * &s + &r, x);
+            assert!(r <= &s << 1u8, "{}", x);
+        }
+    }
+    assert_eq!(sqrt_rem(&BigUint::ZERO), (BigUint::ZERO, BigUint::ZERO));
+}
+
+#[test]
+fn test_nth_root() {
+    use super::{biguint_from_vec, TestRng};
+
+    let mut rng = TestRng::new(0x2545_f491_4f6c_dd1d);
+
+    for len in [1, 2, 3, 5, 12] {
+        let x = biguint_from_vec(rng.digits(len));
+        for k in [3, 4, 5, 7, 31, 32, 33, 100, 1000] {
+            let r = nth_root(&x, k);
+            assert!(Pow::pow(&r, k) <= x);
+            assert!(Pow::pow(&r + 1u32, k) > x, "{} {}", x, k);
+
+            let p = Pow::pow(&x, k);
+            assert_eq!(nth_root(&p, k), x);
+            assert_eq!(nth_root(&(&p - 1u32), k), &x - 1u32);
+        }
+    }
+}
+
+#[test]
+fn test_perfect_power() {
+    let n = |x: u64| BigUint::from(x);
+    assert_eq!(perfect_power(&n(0)), Some((n(0), 2)));
+    assert_eq!(perfect_power(&n(1)), Some((n(1), 2)));
+    assert_eq!(perfect_power(&n(2)), None);
+    assert_eq!(perfect_power(&n(8)), Some((n(2), 3)));
+    assert_eq!(perfect_power(&n(64)), Some((n(2), 6)));
+    assert_eq!(perfect_power(&n(72)), None);
+    assert_eq!(perfect_power(&n(1 << 63)), Some((n(2), 63)));
+
+    let base = BigUint::parse_bytes(b"123456789012345678901234567891", 10).unwrap();
+    for k in [2, 3, 6, 35, 64] {
+        assert_eq!(perfect_power(&Pow::pow(&base, k)), Some((base.clone(), k)));
+        assert_eq!(perfect_power(&(Pow::pow(&base, k) + 1u32)), None);
+    }
+    let base = &base * &base * 12u32;
+    assert_eq!(perfect_power(&Pow::pow(&base, 5u32)), Some((base, 5)));
+
+    for x in 0..10_000u32 {
+        assert_eq!(is_square(&n(x.into())), x.sqrt().pow(2) == x, "{}", x);
+    }
+}
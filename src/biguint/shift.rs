@@ -1,6 +1,6 @@
 use super::{biguint_from_vec, BigUint};
 
-use crate::big_digit;
+use crate::big_digit::{self, BigDigit};
 
 use alloc::borrow::Cow;
 use alloc::vec::Vec;
@@ -35,13 +35,7 @@ fn biguint_shl2(n: Cow<'_, BigUint>, digits: usize, shift: u8) -> BigUint {
     };
 
     if shift > 0 {
-        let mut carry = 0;
-        let carry_shift = big_digit::BITS - shift;
-        for elem in data[digits..].iter_mut() {
-            let new_carry = *elem >> carry_shift;
-            *elem = (*elem << shift) | carry;
-            carry = new_carry;
-        }
+        let carry = shl_bits(&mut data[digits..], shift);
         if carry != 0 {
             data.push(carry);
         }
@@ -79,18 +73,48 @@ fn biguint_shr2(n: Cow<'_, BigUint>, digits: usize, shift: u8) -> BigUint {
     };
 
     if shift > 0 {
-        let mut borrow = 0;
-        let borrow_shift = big_digit::BITS - shift;
-        for elem in data.iter_mut().rev() {
-            let new_borrow = *elem << borrow_shift;
-            *elem = (*elem >> shift) | borrow;
-            borrow = new_borrow;
-        }
+        shr_bits(&mut data, shift);
     }
 
     biguint_from_vec(data)
 }
 
+/// Shifts the digits of `data` left by `shift` bits in place, returning the bits shifted out of
+/// the top digit.
+///
+/// The caller _must_ ensure that `shift` is nonzero and less than `big_digit::BITS`.
+#[inline]
+pub(crate) fn shl_bits(data: &mut [BigDigit], shift: u8) -> BigDigit {
+    debug_assert!(shift > 0 && shift < big_digit::BITS);
+
+    let mut carry = 0;
+    let carry_shift = big_digit::BITS - shift;
+    for elem in data.iter_mut() {
+        let new_carry = *elem >> carry_shift;
+        *elem = (*elem << shift) | carry;
+        carry = new_carry;
+    }
+    carry
+}
+
+/// Shifts the digits of `data` right by `shift` bits in place, returning the bits shifted out of
+/// the bottom digit in the high bits of the result.
+///
+/// The caller _must_ ensure that `shift` is nonzero and less than `big_digit::BITS`.
+#[inline]
+pub(crate) fn shr_bits(data: &mut [BigDigit], shift: u8) -> BigDigit {
+    debug_assert!(shift > 0 && shift < big_digit::BITS);
+
+    let mut borrow = 0;
+    let borrow_shift = big_digit::BITS - shift;
+    for elem in data.iter_mut().rev() {
+        let new_borrow = *elem << borrow_shift;
+        *elem = (*elem >> shift) | borrow;
+        borrow = new_borrow;
+    }
+    borrow
+}
+
 macro_rules! impl_shift {
     (@ref $Shx:ident :: $shx:ident, $ShxAssign:ident :: $shx_assign:ident, $rhs:ty) => {
         impl $Shx<&$rhs> for BigUint {
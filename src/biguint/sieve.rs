@@ -0,0 +1,165 @@
+use super::division::rem_digit;
+use super::BigUint;
+
+use crate::big_digit::BigDigit;
+
+use alloc::vec::Vec;
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
+
+/// Returns all primes `p <= n` in increasing order, using a sieve of Eratosthenes over the odd
+/// numbers.
+pub(crate) fn primes_up_to(n: u64) -> Vec<u64> {
+    let mut primes = Vec::new();
+    if n < 2 {
+        return primes;
+    }
+    primes.push(2);
+
+    // Bit `i` is set when the odd number `2 * i + 1` is composite.
+    let len = (n - 1) / 2 + 1;
+    let mut composite = vec![0u64; len.div_ceil(64) as usize];
+    let mut i = 1;
+    while (2 * i + 1) * (2 * i + 1) <= n {
+        if composite[(i / 64) as usize] & (1 << (i % 64)) == 0 {
+            let p = 2 * i + 1;
+            let mut j = (p * p) / 2;
+            while j < len {
+                composite[(j / 64) as usize] |= 1 << (j % 64);
+                j += p;
+            }
+        }
+        i += 1;
+    }
+
+    for i in 1..len {
+        if composite[(i / 64) as usize] & (1 << (i % 64)) == 0 {
+            primes.push(2 * i + 1);
+        }
+    }
+    primes
+}
+
+/// The modulus of the wheel, `2 * 3 * 5`.
+pub(super) const WHEEL: u64 = 30;
+
+/// The residues modulo `WHEEL` that are prime to it, one for each bit of a segment byte.
+const WHEEL_OFFSETS: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// A sieve of Eratosthenes over segments of large numbers, which keeps only the numbers prime to
+/// the wheel and removes the multiples of the primes from 7 up to a bound.
+///
+/// A segment starts at a multiple of `WHEEL` and is a byte per `WHEEL` numbers, one bit per
+/// residue in `WHEEL_OFFSETS`. Each prime then costs one `rem_digit` of the segment's start, and
+/// a stride through the bytes for each residue.
+#[derive(Clone, Debug)]
+pub(super) struct WheelSieve {
+    /// The sieving primes, each with the inverse of `WHEEL` modulo it.
+    primes: Vec<(u64, u64)>,
+}
+
+impl WheelSieve {
+    /// Creates a sieve by the primes from 7 up to `bound`, which must fit in a `u32`.
+    pub(super) fn new(bound: u64) -> Self {
+        debug_assert!(bound <= u64::from(u32::MAX));
+        let primes = primes_up_to(bound)
+            .into_iter()
+            .skip_while(|p| Integer::is_multiple_of(&WHEEL, p))
+            .map(|p| (p, pow_mod(WHEEL % p, p - 2, p)))
+            .collect();
+        WheelSieve { primes }
+    }
+
+    /// Returns the numbers in `[base, base + WHEEL * wheels)` without a factor among 2, 3, 5 and
+    /// the sieving primes other than themselves, and other than 1, in increasing order.
+    ///
+    /// `base` must be a multiple of `WHEEL`. Below the square of the bound these are exactly the
+    /// primes of the segment.
+    pub(super) fn segment(&self, base: &BigUint, wheels: usize) -> Vec<BigUint> {
+        debug_assert!(rem_digit(base, WHEEL as BigDigit) == 0);
+        let mut composite = vec![0u8; wheels];
+        // A sieving prime can only be in the segment itself when the segment starts below it.
+        let small_base = base.to_u64();
+        if base.is_zero() && wheels > 0 {
+            composite[0] |= 1;
+        }
+
+        for &(p, inverse) in &self.primes {
+            #[allow(clippy::useless_conversion)]
+            let base_rem = u64::from(rem_digit(base, p as BigDigit));
+            for (bit, &offset) in WHEEL_OFFSETS.iter().enumerate() {
+                // base + WHEEL * j + offset = 0 (mod p), for the first such j
+                let mut j = (p - (base_rem + offset) % p) % p * inverse % p;
+                if small_base.is_some_and(|b| b < p && b + WHEEL * j + offset == p) {
+                    j += p;
+                }
+                for byte in composite.iter_mut().skip(j as usize).step_by(p as usize) {
+                    *byte |= 1 << bit;
+                }
+            }
+        }
+
+        let mut survivors = Vec::new();
+        if base.is_zero() {
+            survivors.extend([2u32, 3, 5].map(BigUint::from));
+        }
+        for (j, &byte) in composite.iter().enumerate() {
+            for (bit, &offset) in WHEEL_OFFSETS.iter().enumerate() {
+                if byte & (1 << bit) == 0 {
+                    survivors.push(base + (WHEEL * j as u64 + offset));
+                }
+            }
+        }
+        survivors
+    }
+}
+
+/// Returns `b^e mod m`, for `m` that fits in a `u32`.
+fn pow_mod(mut b: u64, mut e: u64, m: u64) -> u64 {
+    let mut result = 1;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result * b % m;
+        }
+        b = b * b % m;
+        e >>= 1;
+    }
+    result
+}
+
+#[test]
+fn test_primes_up_to() {
+    assert!(primes_up_to(0).is_empty());
+    assert!(primes_up_to(1).is_empty());
+    assert_eq!(primes_up_to(2), [2]);
+    assert_eq!(primes_up_to(30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert_eq!(primes_up_to(31).last(), Some(&31));
+    assert_eq!(primes_up_to(100_000).len(), 9592);
+}
+
+#[test]
+fn test_wheel_sieve() {
+    let primes = primes_up_to(20_000);
+    let sieve = WheelSieve::new(100);
+    for start in [0u64, 30, 90, 6_000] {
+        let base = BigUint::from(start);
+        let segment = sieve.segment(&base, 100);
+        let expected: Vec<BigUint> = primes
+            .iter()
+            .filter(|&&p| p >= start && p < start + 3000)
+            .map(|&p| BigUint::from(p))
+            .collect();
+        assert_eq!(segment, expected, "{}", start);
+    }
+
+    // every survivor of a large segment is prime to the sieving primes
+    let base = (BigUint::from(1u32) << 200u8) / 30u32 * 30u32;
+    let segment = sieve.segment(&base, 50);
+    assert!(!segment.is_empty());
+    for n in &segment {
+        assert!(n >= &base && n < &(&base + 1500u32));
+        assert!(primes_up_to(100)
+            .iter()
+            .all(|&p| rem_digit(n, p as BigDigit) != 0));
+    }
+}
@@ -45,6 +45,35 @@ fn sbb(borrow: u8, lhs: BigDigit, rhs: BigDigit, out: &mut BigDigit) -> u8 {
     u8::from(b || d)
 }
 
+/// Two argument subtraction of raw slices, modulo the length of `a`:
+/// a -= b
+///
+/// Returns the borrow out of the top of `a`, which is nonzero if `b` was larger.
+///
+/// The caller _must_ ensure that `a` is at least as long as `b`.
+#[inline]
+pub(super) fn __sub2(a: &mut [BigDigit], b: &[BigDigit]) -> u8 {
+    debug_assert!(a.len() >= b.len());
+
+    let mut borrow = 0;
+    let (a_lo, a_hi) = a.split_at_mut(b.len());
+
+    for (a, b) in a_lo.iter_mut().zip(b) {
+        borrow = sbb(borrow, *a, *b, a);
+    }
+
+    if borrow != 0 {
+        for a in a_hi {
+            borrow = sbb(borrow, *a, 0, a);
+            if borrow == 0 {
+                break;
+            }
+        }
+    }
+
+    borrow
+}
+
 pub(super) fn sub2(a: &mut [BigDigit], b: &[BigDigit]) {
     let mut borrow = 0;
 
@@ -170,7 +199,7 @@ impl Sub<BigUint> for u32 {
     cfg_digit!(
         #[inline]
         fn sub(self, mut other: BigUint) -> BigUint {
-            if other.data.len() == 0 {
+            if other.data.is_empty() {
                 other.data.push(self);
             } else {
                 sub2rev(&[self], &mut other.data[..]);
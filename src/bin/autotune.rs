@@ -0,0 +1,292 @@
+//! Measures the algorithm crossover points on this host and prints a tuning profile.
+//!
+//! Usage: `autotune [--rust]`
+//!
+//! By default the profile is printed in the format read by `Tuning::from_profile`. With `--rust`
+//! it is printed as a `const` that can be compiled in and installed with `Tuning::install`.
+//! Build with `--release`, or the measurements will say more about the debug build than the CPU.
+
+use std::env;
+use std::hint::black_box;
+use std::process;
+use std::time::{Duration, Instant};
+
+use num_traits::One;
+use rust_monty_parallel::{BigUint, Tuning};
+
+/// A small xorshift generator, so the measurements don't depend on any `rand` crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 32) as u32
+    }
+
+    /// A random number of exactly `digits` 64-bit digits.
+    fn biguint(&mut self, digits: usize) -> BigUint {
+        let mut data: Vec<u32> = (0..digits * 2).map(|_| self.next_u32()).collect();
+        if let Some(top) = data.last_mut() {
+            *top |= 1 << 31;
+        }
+        BigUint::new(data)
+    }
+}
+
+/// Returns the best time per call of `f`, with `tuning` installed.
+fn time<F: FnMut()>(tuning: Tuning, mut f: F) -> Duration {
+    tuning.install();
+
+    // Find a batch size that takes a measurable amount of time.
+    let mut batch = 1u32;
+    loop {
+        let start = Instant::now();
+        for _ in 0..batch {
+            f();
+        }
+        if start.elapsed() >= Duration::from_millis(2) || batch >= 1 << 20 {
+            break;
+        }
+        batch *= 2;
+    }
+
+    (0..7)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..batch {
+                f();
+            }
+            start.elapsed() / batch
+        })
+        .min()
+        .unwrap()
+}
+
+/// Walks `sizes` upwards and returns the size just below the first one at which the `above`
+/// variant is consistently faster than the `below` variant, or the last size if it never is. If
+/// the `above` variant already wins at the first size, that size is returned.
+fn crossover<F>(sizes: &[usize], mut faster_above: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    let mut first_win: Option<usize> = None;
+    for (i, &n) in sizes.iter().enumerate() {
+        if faster_above(n) {
+            // Require two wins in a row to avoid reacting to noise.
+            match first_win {
+                Some(first) => return sizes[first.saturating_sub(1)],
+                None => first_win = Some(i),
+            }
+        } else {
+            first_win = None;
+        }
+    }
+    *sizes.last().unwrap()
+}
+
+fn tune_karatsuba(rng: &mut Rng, base: Tuning) -> usize {
+    let sizes: Vec<usize> = (4..=48).map(|i| i * 4).collect();
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n), rng.biguint(n));
+        // Long multiplication at this size, versus one level of Karatsuba on top of it.
+        let long = Tuning {
+            mul_karatsuba_threshold: n,
+            ..base
+        };
+        let karatsuba = Tuning {
+            mul_karatsuba_threshold: n - 1,
+            ..base
+        };
+        time(karatsuba, || drop(black_box(&a * &b))) < time(long, || drop(black_box(&a * &b)))
+    })
+}
+
+fn tune_toom3(rng: &mut Rng, base: Tuning) -> usize {
+    let start = base.mul_karatsuba_threshold * 2;
+    let sizes: Vec<usize> = (0..24).map(|i| start + i * start / 4).collect();
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n), rng.biguint(n));
+        let karatsuba = Tuning {
+            mul_toom3_threshold: n,
+            ..base
+        };
+        let toom3 = Tuning {
+            mul_toom3_threshold: n - 1,
+            ..base
+        };
+        time(toom3, || drop(black_box(&a * &b))) < time(karatsuba, || drop(black_box(&a * &b)))
+    })
+}
+
+fn tune_toom4(rng: &mut Rng, base: Tuning) -> usize {
+    let mut sizes = vec![base.mul_toom3_threshold * 2];
+    for _ in 0..16 {
+        sizes.push(sizes.last().unwrap() * 5 / 4);
+    }
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n), rng.biguint(n));
+        let toom3 = Tuning {
+            mul_toom4_threshold: n,
+            mul_ntt_threshold: usize::MAX,
+            ..base
+        };
+        let toom4 = Tuning {
+            mul_toom4_threshold: n - 1,
+            mul_ntt_threshold: usize::MAX,
+            ..base
+        };
+        time(toom4, || drop(black_box(&a * &b))) < time(toom3, || drop(black_box(&a * &b)))
+    })
+}
+
+fn tune_ntt(rng: &mut Rng, base: Tuning) -> usize {
+    let mut sizes = vec![base.mul_toom3_threshold * 2];
+    for _ in 0..16 {
+        sizes.push(sizes.last().unwrap() * 5 / 4);
+    }
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n), rng.biguint(n));
+        let toom3 = Tuning {
+            mul_ntt_threshold: n,
+            ..base
+        };
+        let ntt = Tuning {
+            mul_ntt_threshold: n - 1,
+            ..base
+        };
+        time(ntt, || drop(black_box(&a * &b))) < time(toom3, || drop(black_box(&a * &b)))
+    })
+}
+
+fn tune_burnikel_ziegler(rng: &mut Rng, base: Tuning) -> usize {
+    let sizes: Vec<usize> = (2..=48).map(|i| i * 8).collect();
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n * 2), rng.biguint(n));
+        let long = Tuning {
+            div_burnikel_ziegler_threshold: n,
+            ..base
+        };
+        let recursive = Tuning {
+            div_burnikel_ziegler_threshold: n - 1,
+            ..base
+        };
+        time(recursive, || drop(black_box(&a / &b))) < time(long, || drop(black_box(&a / &b)))
+    })
+}
+
+fn tune_newton(rng: &mut Rng, base: Tuning) -> usize {
+    // Newton's iteration only pays off once multiplication is much faster than division, so
+    // there is no need to look below the NTT threshold.
+    let mut sizes = vec![base.mul_ntt_threshold];
+    for _ in 0..14 {
+        sizes.push(sizes.last().unwrap() * 5 / 4);
+    }
+    crossover(&sizes, |n| {
+        let (a, b) = (rng.biguint(n * 2), rng.biguint(n));
+        let recursive = Tuning {
+            div_newton_threshold: n,
+            ..base
+        };
+        let newton = Tuning {
+            div_newton_threshold: n - 1,
+            ..base
+        };
+        time(newton, || drop(black_box(&a / &b))) < time(recursive, || drop(black_box(&a / &b)))
+    })
+}
+
+fn tune_radix(rng: &mut Rng, base: Tuning) -> usize {
+    let sizes: Vec<usize> = (1..=32).map(|i| i * 8).collect();
+    crossover(&sizes, |n| {
+        let a = rng.biguint(n);
+        let simple = Tuning {
+            radix_divide_threshold: usize::MAX,
+            ..base
+        };
+        let divided = Tuning {
+            radix_divide_threshold: n,
+            ..base
+        };
+        time(divided, || drop(black_box(a.to_str_radix(10))))
+            < time(simple, || drop(black_box(a.to_str_radix(10))))
+    }) + 1
+}
+
+fn tune_monty_window(rng: &mut Rng, base: Tuning) -> usize {
+    // A 2048-bit modulus is the most common size for modular exponentiation.
+    let mut m = rng.biguint(32);
+    if !m.bit(0) {
+        m += BigUint::one();
+    }
+    let (b, e) = (rng.biguint(31), rng.biguint(32));
+    [1, 2, 4, 8]
+        .iter()
+        .map(|&bits| {
+            let tuning = Tuning {
+                monty_window_bits: bits,
+                ..base
+            };
+            (time(tuning, || drop(black_box(b.modpow(&e, &m)))), bits)
+        })
+        .min()
+        .unwrap()
+        .1
+}
+
+fn main() {
+    let rust = match env::args().nth(1).as_deref() {
+        None => false,
+        Some("--rust") => true,
+        Some(_) => {
+            eprintln!("usage: autotune [--rust]");
+            process::exit(2);
+        }
+    };
+
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut tuning = Tuning::DEFAULT;
+    eprintln!("measuring the Karatsuba threshold...");
+    tuning.mul_karatsuba_threshold = tune_karatsuba(&mut rng, tuning);
+    eprintln!("measuring the Toom-3 threshold...");
+    tuning.mul_toom3_threshold = tune_toom3(&mut rng, tuning);
+    eprintln!("measuring the Toom-4 threshold...");
+    tuning.mul_toom4_threshold = tune_toom4(&mut rng, tuning);
+    eprintln!("measuring the NTT threshold...");
+    tuning.mul_ntt_threshold = tune_ntt(&mut rng, tuning);
+    eprintln!("measuring the Burnikel-Ziegler division threshold...");
+    tuning.div_burnikel_ziegler_threshold = tune_burnikel_ziegler(&mut rng, tuning);
+    eprintln!("measuring the Newton division threshold...");
+    tuning.div_newton_threshold = tune_newton(&mut rng, tuning);
+    eprintln!("measuring the radix conversion threshold...");
+    tuning.radix_divide_threshold = tune_radix(&mut rng, tuning);
+    eprintln!("measuring the Montgomery window size...");
+    tuning.monty_window_bits = tune_monty_window(&mut rng, tuning);
+
+    if rust {
+        println!("/// Tuning profile measured by `autotune`.");
+        println!(
+            "pub const HOST_TUNING: rust_monty_parallel::Tuning = rust_monty_parallel::Tuning {{"
+        );
+        for line in tuning.to_profile().lines().skip(1) {
+            let (key, value) = line.split_once(" = ").unwrap();
+            println!("    {}: {},", key, value);
+        }
+        println!("    ..rust_monty_parallel::Tuning::DEFAULT");
+        println!("}};");
+    } else {
+        println!("# Tuning profile measured by `autotune`.");
+        print!("{}", tuning.to_profile());
+    }
+}
+
+#[test]
+fn test_crossover() {
+    let sizes = [10, 20, 30, 40, 50];
+    assert_eq!(crossover(&sizes, |_| true), 10);
+    assert_eq!(crossover(&sizes, |_| false), 50);
+    assert_eq!(crossover(&sizes, |n| n >= 30), 20);
+    // a single win is noise
+    assert_eq!(crossover(&sizes, |n| n == 20 || n >= 40), 30);
+}
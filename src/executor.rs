@@ -0,0 +1,228 @@
+//! Pluggable execution of the parallel parts of big integer arithmetic.
+//!
+//! Some operations on large numbers split their work into independent tasks. Those tasks are
+//! handed to the [`Executor`] installed with [`set_executor`], which decides whether and where
+//! they run concurrently. Three executors are provided:
+//!
+//! * [`Serial`] runs every task on the calling thread. It is always available, and is the
+//!   default unless the `parallel` feature is enabled.
+//! * [`StdThreads`] runs tasks on scoped `std` threads, without any thread pool, so it only pays
+//!   off for coarse tasks. It requires the `std` feature.
+//! * [`Rayon`] runs tasks on the global rayon thread pool. It requires the `parallel` feature,
+//!   and is the default when that is enabled.
+//!
+//! Embedded or sandboxed users can implement [`Executor`] for their own worker pool instead.
+//! Work is only split when the executor reports more than one thread and the operands are at
+//! least `Tuning::parallel_threshold` digits long, so a [`Serial`] executor costs nothing.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::tuning;
+
+/// A strategy for running independent tasks, possibly concurrently.
+///
+/// This trait is object safe, so that an executor can be installed at runtime with
+/// [`set_executor`].
+pub trait Executor: Sync {
+    /// Runs all of `tasks`, returning once every one of them has finished.
+    ///
+    /// Each task is called exactly once. Tasks may run in any order or at the same time.
+    fn scope(&self, tasks: &mut [&mut (dyn FnMut() + Send)]);
+
+    /// Runs `a` and `b`, returning once both have finished.
+    fn join(&self, a: &mut (dyn FnMut() + Send), b: &mut (dyn FnMut() + Send)) {
+        self.scope(&mut [a, b]);
+    }
+
+    /// Returns the number of tasks that can usefully run at the same time.
+    fn current_num_threads(&self) -> usize {
+        1
+    }
+}
+
+/// An [`Executor`] that runs every task in order on the calling thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serial;
+
+impl Executor for Serial {
+    fn scope(&self, tasks: &mut [&mut (dyn FnMut() + Send)]) {
+        for task in tasks {
+            task();
+        }
+    }
+}
+
+/// An [`Executor`] that runs tasks on scoped threads spawned with [`std::thread::scope`].
+///
+/// One thread is spawned per task beyond the first, which runs on the calling thread. Spawning
+/// costs microseconds per task, which fine-grained work such as the rows of a Montgomery product
+/// doesn't repay, so this is only suited to coarse tasks. Prefer [`Rayon`] or a thread pool of
+/// your own otherwise.
+///
+/// The thread count is read from [`std::thread::available_parallelism`] once, on first use.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdThreads;
+
+#[cfg(feature = "std")]
+impl Executor for StdThreads {
+    fn scope(&self, tasks: &mut [&mut (dyn FnMut() + Send)]) {
+        if let Some((first, rest)) = tasks.split_first_mut() {
+            std::thread::scope(|s| {
+                for task in rest {
+                    s.spawn(task);
+                }
+                first();
+            });
+        }
+    }
+
+    fn current_num_threads(&self) -> usize {
+        // Querying the parallelism takes syscalls, and on Linux reads the cgroup limits.
+        static THREADS: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+        *THREADS.get_or_init(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+}
+
+/// An [`Executor`] that runs tasks on the global rayon thread pool.
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rayon;
+
+#[cfg(feature = "parallel")]
+impl Executor for Rayon {
+    fn scope(&self, tasks: &mut [&mut (dyn FnMut() + Send)]) {
+        rayon::scope(|s| {
+            for task in tasks {
+                s.spawn(move |_| task());
+            }
+        });
+    }
+
+    fn join(&self, a: &mut (dyn FnMut() + Send), b: &mut (dyn FnMut() + Send)) {
+        rayon::join(a, b);
+    }
+
+    fn current_num_threads(&self) -> usize {
+        rayon::current_num_threads()
+    }
+}
+
+#[cfg(feature = "parallel")]
+static DEFAULT: &dyn Executor = &Rayon;
+#[cfg(not(feature = "parallel"))]
+static DEFAULT: &dyn Executor = &Serial;
+
+static CURRENT: AtomicPtr<&'static dyn Executor> = AtomicPtr::new(ptr::null_mut());
+
+/// Installs `executor` for all subsequent operations.
+///
+/// Operations already in progress may finish on the previous executor. Each call leaks a
+/// pointer-sized allocation, so this is meant to be called once at startup.
+pub fn set_executor(executor: &'static dyn Executor) {
+    let executor = Box::into_raw(Box::new(executor));
+    // The previous pointer may still be in use by another thread, so it is never freed.
+    CURRENT.store(executor, Ordering::Release);
+}
+
+/// Returns the executor currently in effect.
+pub fn executor() -> &'static dyn Executor {
+    let current = CURRENT.load(Ordering::Acquire);
+    if current.is_null() {
+        DEFAULT
+    } else {
+        // SAFETY: non-null pointers only come from `set_executor`, which never frees them.
+        unsafe { *current }
+    }
+}
+
+/// Runs `a` and `b` on the current executor and returns both results.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    let (mut a, mut b) = (Some(a), Some(b));
+    let (mut ra, mut rb) = (None, None);
+    let mut task_a = || ra = a.take().map(|a| a());
+    let mut task_b = || rb = b.take().map(|b| b());
+    executor().join(&mut task_a, &mut task_b);
+    (ra.unwrap(), rb.unwrap())
+}
+
+/// Returns how many tasks a loop over `len` digits should be split into, or 1 if it shouldn't.
+pub(crate) fn split_count(len: usize) -> usize {
+    let threshold = tuning::parallel_threshold();
+    if len < threshold.saturating_mul(2) {
+        return 1;
+    }
+    executor().current_num_threads().min(len / threshold).max(1)
+}
+
+/// Calls `f` on every item, as separate tasks on the current executor.
+pub(crate) fn for_each<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(&mut T) + Sync,
+{
+    let f = &f;
+    let mut tasks: Vec<_> = items.iter_mut().map(|item| move || f(item)).collect();
+    let mut tasks: Vec<&mut (dyn FnMut() + Send)> = tasks
+        .iter_mut()
+        .map(|task| task as &mut (dyn FnMut() + Send))
+        .collect();
+    executor().scope(&mut tasks);
+}
+
+/// Checks the result of a parallel computation against the serial path, when the
+/// `verify-parallel` feature is enabled. Otherwise this does nothing.
+///
+/// Panics with the operands and both results if they disagree.
+#[inline]
+pub(crate) fn verify<T, S, D>(op: &str, parallel: &T, serial: S, operands: D)
+where
+    T: PartialEq + Debug,
+    S: FnOnce() -> T,
+    D: Debug,
+{
+    #[cfg(feature = "verify-parallel")]
+    {
+        let serial = serial();
+        if *parallel != serial {
+            panic!(
+                "parallel {} disagrees with the serial path\n\
+                 operands: {:?}\n\
+                 parallel: {:?}\n\
+                 serial: {:?}",
+                op, operands, parallel, serial
+            );
+        }
+    }
+    #[cfg(not(feature = "verify-parallel"))]
+    let _ = (op, parallel, serial, operands);
+}
+
+#[test]
+fn test_join() {
+    let (a, b) = join(|| 1 + 1, || "two");
+    assert_eq!((a, b), (2, "two"));
+
+    let mut items = [1, 2, 3, 4];
+    for_each(&mut items, |x| *x *= 10);
+    assert_eq!(items, [10, 20, 30, 40]);
+}
+
+#[test]
+#[cfg(feature = "verify-parallel")]
+#[should_panic(expected = "parallel sum disagrees with the serial path")]
+fn test_verify_mismatch() {
+    verify("sum", &3, || 4, (1, 2));
+}
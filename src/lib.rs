@@ -65,6 +65,22 @@
 //! depending on `num-bigint` with `default-features = false`. Either way, the `alloc` crate is
 //! always required for heap allocation of the `BigInt`/`BigUint` digits.
 //!
+//! ### Parallel Execution
+//!
+//! The `parallel` feature (which implies `std`) runs the independent parts of operations on
+//! large numbers on the rayon thread pool. Without it, a different backend can be installed
+//! through the [`executor`] module, such as scoped `std` threads or a custom worker pool.
+//!
+//! The `verify-parallel` feature is a debugging aid: every operation that splits its work
+//! across threads also runs the serial path, and panics with the operands if the two disagree.
+//!
+//! ### Digit Size
+//!
+//! Internally, numbers are stored as `u64` digits on 64-bit targets and as `u32` digits
+//! elsewhere. The `force-u32-digits` feature selects `u32` digits on 64-bit targets too, which
+//! is mainly useful for testing the 32-bit configuration on 64-bit machines. The low-level
+//! [`limbs`] module works on `u64` limbs in either configuration.
+//!
 //! ### Random Generation
 //!
 //! `num-bigint` supports the generation of random big integers when the `rand`
@@ -114,6 +130,9 @@ mod macros;
 pub mod bigint;
 mod bigrand;
 pub mod biguint;
+pub mod executor;
+pub mod limbs;
+pub mod tuning;
 
 #[cfg(target_pointer_width = "32")]
 type UsizePromotion = u32;
@@ -212,18 +231,28 @@ impl<T> fmt::Display for TryFromBigIntError<T> {
 }
 
 pub use crate::biguint::BigUint;
+pub use crate::biguint::DigitDivisor;
+pub use crate::biguint::IterBits;
+pub use crate::biguint::IterOnes;
+pub use crate::biguint::PrimeIter;
 pub use crate::biguint::ToBigUint;
-// pub use crate::biguint::U32Digits;
-// pub use crate::biguint::U64Digits;
+pub use crate::biguint::U32Digits;
+pub use crate::biguint::U64Digits;
 
 pub use crate::bigint::BigInt;
 pub use crate::bigint::Sign;
 pub use crate::bigint::ToBigInt;
 
+pub use crate::tuning::Tuning;
+
 #[cfg(feature = "rand")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
 pub use crate::bigrand::{RandBigInt, RandomBits, UniformBigInt, UniformBigUint};
 
+#[cfg(feature = "rand")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+pub use crate::biguint::RandPrime;
+
 mod big_digit {
     // A [`BigDigit`] is a [`BigUint`]'s composing element.
     cfg_digit!(
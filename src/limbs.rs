@@ -0,0 +1,506 @@
+//! Low-level arithmetic on slices of `u64` limbs.
+//!
+//! These functions expose the arithmetic behind [`BigUint`] for callers that manage their own
+//! storage, such as fixed-size buffers or custom number representations, in the spirit of GMP's
+//! `mpn` layer. Numbers are little-endian slices of `u64` limbs, and may have high zero limbs.
+//! Results are written to caller-provided outputs, whose lengths are part of each function's
+//! preconditions. A violated length precondition panics, while other preconditions are only
+//! checked in debug builds and otherwise produce meaningless results.
+//!
+//! The limbs are `u64` regardless of the internal digit size of [`BigUint`], and with `u64` digits
+//! the functions are thin wrappers over the same digit kernels. With `u32` digits, the functions
+//! that call into the big number algorithms convert their operands first, while the linear ones
+//! work on the limbs directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use rust_monty_parallel::limbs;
+//!
+//! let a = [u64::MAX, 1];
+//! let b = [1, 2];
+//! let mut sum = [0; 2];
+//! assert_eq!(limbs::add_n(&mut sum, &a, &b), 0);
+//! assert_eq!(sum, [0, 4]);
+//!
+//! let mut prod = [0; 4];
+//! limbs::mul(&mut prod, &a, &b);
+//! assert_eq!(prod, [u64::MAX, u64::MAX, 3, 0]);
+//! ```
+//!
+//! [`BigUint`]: crate::BigUint
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::big_digit::BigDigit;
+use crate::biguint::{
+    biguint_from_vec, div_rem_digit, div_rem_normalized, mul_high_digits, mul_low_digits,
+    mul_slices, shl_bits, sqr_slices, BigUint, IntDigits,
+};
+
+cfg_digit!(
+    fn to_digits(x: &[u64]) -> Vec<BigDigit> {
+        x.iter()
+            .flat_map(|&w| [w as u32, (w >> 32) as u32])
+            .collect()
+    }
+
+    fn to_digits(x: &[u64]) -> Vec<BigDigit> {
+        x.to_vec()
+    }
+);
+
+cfg_digit!(
+    /// Writes `digits` to `out`, padded with zero limbs.
+    fn write_digits(out: &mut [u64], digits: &[BigDigit]) {
+        out.fill(0);
+        for (o, c) in out.iter_mut().zip(digits.chunks(2)) {
+            *o = u64::from(c[0]) | u64::from(c.get(1).copied().unwrap_or(0)) << 32;
+        }
+    }
+
+    /// Writes `digits` to `out`, padded with zero limbs.
+    fn write_digits(out: &mut [u64], digits: &[BigDigit]) {
+        out.fill(0);
+        out[..digits.len()].copy_from_slice(digits);
+    }
+);
+
+cfg_digit!(
+    /// With `u32` digits, a limb multiplier or a shift of up to 63 bits doesn't fit the digit
+    /// kernels, so these work on the limbs directly.
+    mod kernels {
+        use super::{to_digits, write_digits};
+        use crate::biguint::montgomery_digits;
+        use alloc::vec::Vec;
+
+        pub(super) fn add_n(r: &mut [u64], a: &[u64], b: &[u64]) -> u64 {
+            let mut carry = false;
+            for ((r, &a), &b) in r.iter_mut().zip(a).zip(b) {
+                let (sum, c1) = a.overflowing_add(b);
+                let (sum, c2) = sum.overflowing_add(u64::from(carry));
+                *r = sum;
+                carry = c1 || c2;
+            }
+            u64::from(carry)
+        }
+
+        pub(super) fn sub_n(r: &mut [u64], a: &[u64], b: &[u64]) -> u64 {
+            let mut borrow = false;
+            for ((r, &a), &b) in r.iter_mut().zip(a).zip(b) {
+                let (diff, b1) = a.overflowing_sub(b);
+                let (diff, b2) = diff.overflowing_sub(u64::from(borrow));
+                *r = diff;
+                borrow = b1 || b2;
+            }
+            u64::from(borrow)
+        }
+
+        pub(super) fn addmul_1(r: &mut [u64], a: &[u64], b: u64) -> u64 {
+            let mut carry = 0;
+            for (r, &a) in r.iter_mut().zip(a) {
+                // (2^64 - 1)^2 + 2 * (2^64 - 1) = 2^128 - 1, so this can't overflow.
+                let t = u128::from(a) * u128::from(b) + u128::from(*r) + u128::from(carry);
+                *r = t as u64;
+                carry = (t >> 64) as u64;
+            }
+            carry
+        }
+
+        pub(super) fn submul_1(r: &mut [u64], a: &[u64], b: u64) -> u64 {
+            let mut borrow = 0;
+            for (r, &a) in r.iter_mut().zip(a) {
+                let t = u128::from(a) * u128::from(b) + u128::from(borrow);
+                let (diff, overflow) = r.overflowing_sub(t as u64);
+                *r = diff;
+                borrow = (t >> 64) as u64 + u64::from(overflow);
+            }
+            borrow
+        }
+
+        pub(super) fn lshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+            let mut carry = 0;
+            for (r, &a) in r.iter_mut().zip(a) {
+                *r = (a << cnt) | carry;
+                carry = a >> (64 - cnt);
+            }
+            carry
+        }
+
+        pub(super) fn rshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+            let mut carry = 0;
+            for (r, &a) in r.iter_mut().zip(a).rev() {
+                *r = (a >> cnt) | carry;
+                carry = a << (64 - cnt);
+            }
+            carry
+        }
+
+        pub(super) fn montgomery(a: &[u64], b: &[u64], m: &[u64], minv: u64) -> Vec<u64> {
+            let digits =
+                montgomery_digits(&to_digits(a), &to_digits(b), &to_digits(m), minv as u32);
+            let mut z = vec![0; m.len()];
+            write_digits(&mut z, &digits);
+            z
+        }
+    }
+
+    /// With `u64` digits, the limbs are the digits, so these are the digit kernels.
+    mod kernels {
+        use crate::biguint::{__add2, shl_bits, shr_bits};
+
+        pub(super) use crate::biguint::{
+            add_mul_vvw_serial as addmul_1, montgomery_digits as montgomery,
+            sub_mul_digit_same_len as submul_1, sub_vv as sub_n,
+        };
+
+        pub(super) fn add_n(r: &mut [u64], a: &[u64], b: &[u64]) -> u64 {
+            r.copy_from_slice(a);
+            __add2(r, b)
+        }
+
+        pub(super) fn lshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+            r.copy_from_slice(a);
+            shl_bits(r, cnt as u8)
+        }
+
+        pub(super) fn rshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+            r.copy_from_slice(a);
+            shr_bits(r, cnt as u8)
+        }
+    }
+);
+
+/// Writes `a + b` to `r`, returning the carry out of the top limb.
+///
+/// All three slices must have the same length.
+pub fn add_n(r: &mut [u64], a: &[u64], b: &[u64]) -> u64 {
+    assert!(r.len() == a.len() && a.len() == b.len());
+    kernels::add_n(r, a, b)
+}
+
+/// Writes `a - b` to `r`, returning the borrow out of the top limb.
+///
+/// All three slices must have the same length. If `b` is larger than `a`, `r` holds the
+/// difference modulo 2<sup>64n</sup> and the borrow is 1.
+pub fn sub_n(r: &mut [u64], a: &[u64], b: &[u64]) -> u64 {
+    assert!(r.len() == a.len() && a.len() == b.len());
+    kernels::sub_n(r, a, b)
+}
+
+/// Writes `a * b` to `r`, returning the limb carried out of the top.
+///
+/// `r` must be as long as `a`.
+pub fn mul_1(r: &mut [u64], a: &[u64], b: u64) -> u64 {
+    assert_eq!(r.len(), a.len());
+    r.fill(0);
+    addmul_1(r, a, b)
+}
+
+/// Adds `a * b` to `r`, returning the limb carried out of the top.
+///
+/// `r` must be as long as `a`.
+pub fn addmul_1(r: &mut [u64], a: &[u64], b: u64) -> u64 {
+    assert_eq!(r.len(), a.len());
+    kernels::addmul_1(r, a, b)
+}
+
+/// Subtracts `a * b` from `r`, returning the limb borrowed out of the top.
+///
+/// `r` must be as long as `a`.
+pub fn submul_1(r: &mut [u64], a: &[u64], b: u64) -> u64 {
+    assert_eq!(r.len(), a.len());
+    kernels::submul_1(r, a, b)
+}
+
+/// Writes `a * b` to `r`, choosing the algorithm by operand size like `BigUint` does.
+///
+/// `r` must be exactly `a.len() + b.len()` limbs long. Operands longer than the Karatsuba
+/// threshold use temporary allocations.
+pub fn mul(r: &mut [u64], a: &[u64], b: &[u64]) {
+    assert_eq!(r.len(), a.len() + b.len());
+    cfg_digit_expr!(
+        {
+            let mut prod = vec![0; r.len() * 2];
+            mul_slices(&mut prod, &to_digits(a), &to_digits(b));
+            write_digits(r, &prod);
+        },
+        mul_slices(r, a, b)
+    );
+}
+
+/// Writes `a * a` to `r`, which is faster than [`mul`] with equal operands.
+///
+/// `r` must be exactly `2 * a.len()` limbs long. Operands longer than the Karatsuba threshold use
+/// temporary allocations.
+pub fn sqr(r: &mut [u64], a: &[u64]) {
+    assert_eq!(r.len(), a.len() * 2);
+    cfg_digit_expr!(
+        {
+            let mut prod = vec![0; r.len() * 2];
+            sqr_slices(&mut prod, &to_digits(a));
+            write_digits(r, &prod);
+        },
+        sqr_slices(r, a)
+    );
+}
+
+/// Writes the low `r.len()` limbs of `a * b` to `r`, without computing the digit products that
+/// only affect higher limbs.
+pub fn mul_low(r: &mut [u64], a: &[u64], b: &[u64]) {
+    cfg_digit_expr!(
+        {
+            let low = mul_low_digits(&to_digits(a), &to_digits(b), r.len() * 2);
+            write_digits(r, &low);
+        },
+        r.copy_from_slice(&mul_low_digits(a, b, r.len()))
+    );
+}
+
+/// Writes `a * b` with the low `n` limbs discarded to `r`, without computing most of the digit
+/// products that only affect those limbs. The result is exact.
+///
+/// `n` must be at most `a.len() + b.len()`, and `r` must be `a.len() + b.len() - n` limbs long.
+pub fn mul_high(r: &mut [u64], a: &[u64], b: &[u64], n: usize) {
+    assert!(n <= a.len() + b.len());
+    assert_eq!(r.len(), a.len() + b.len() - n);
+    cfg_digit_expr!(
+        write_digits(
+            r,
+            mul_high_digits(&to_digits(a), &to_digits(b), n * 2).digits()
+        ),
+        write_digits(r, mul_high_digits(a, b, n).digits())
+    );
+}
+
+/// Divides `a` by `d`, writing the quotient to `q` and the remainder to `r`.
+///
+/// `d` must have a nonzero top limb and be no longer than `a`. `q` must be
+/// `a.len() - d.len() + 1` limbs long and `r` must be as long as `d`. The division works on
+/// copies of `a` and `d`, normalized so that the divisor's top bit is set, and the quotient and
+/// remainder are allocated before they are written out.
+///
+/// Panics if `d` has a zero top limb, which includes an empty `d`.
+pub fn divrem(q: &mut [u64], r: &mut [u64], a: &[u64], d: &[u64]) {
+    match d.last() {
+        Some(&top) if top != 0 => {}
+        _ => panic!("divisor must have a nonzero top limb"),
+    }
+    assert!(d.len() <= a.len());
+    assert_eq!(q.len(), a.len() - d.len() + 1);
+    assert_eq!(r.len(), d.len());
+
+    let mut u = to_digits(a);
+    let mut v = to_digits(d);
+    // With `u32` digits, the high half of the top limb may be zero.
+    if v.last() == Some(&0) {
+        v.pop();
+    }
+
+    // Shift both operands so that the top bit of the divisor is set, as the division expects.
+    let shift = v.last().unwrap().leading_zeros() as u8;
+    if shift > 0 {
+        let carry = shl_bits(&mut u, shift);
+        u.push(carry);
+        shl_bits(&mut v, shift);
+    }
+
+    let u = biguint_from_vec(u);
+    if u.digits().len() < v.len() {
+        q.fill(0);
+        r.copy_from_slice(&a[..r.len()]);
+        return;
+    }
+    let (quotient, mut remainder) = if let [v0] = v[..] {
+        let (quotient, rem) = div_rem_digit(u, v0);
+        (quotient, BigUint::from(rem))
+    } else {
+        div_rem_normalized(u, &v)
+    };
+    if shift > 0 {
+        remainder >>= shift;
+    }
+    write_digits(q, quotient.digits());
+    write_digits(r, remainder.digits());
+}
+
+/// Compares `a` and `b`, which must have the same length.
+pub fn cmp(a: &[u64], b: &[u64]) -> Ordering {
+    assert_eq!(a.len(), b.len());
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// Writes `a` shifted left by `cnt` bits to `r`, returning the bits shifted out of the top limb
+/// in the low bits of the result.
+///
+/// `r` must be as long as `a`, and `cnt` must be less than 64.
+pub fn lshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+    assert_eq!(r.len(), a.len());
+    assert!(cnt < 64);
+    if cnt == 0 {
+        r.copy_from_slice(a);
+        return 0;
+    }
+    kernels::lshift(r, a, cnt)
+}
+
+/// Writes `a` shifted right by `cnt` bits to `r`, returning the bits shifted out of the bottom
+/// limb in the high bits of the result.
+///
+/// `r` must be as long as `a`, and `cnt` must be less than 64.
+pub fn rshift(r: &mut [u64], a: &[u64], cnt: u32) -> u64 {
+    assert_eq!(r.len(), a.len());
+    assert!(cnt < 64);
+    if cnt == 0 {
+        r.copy_from_slice(a);
+        return 0;
+    }
+    kernels::rshift(r, a, cnt)
+}
+
+/// Returns `-1 / m0` modulo 2<sup>64</sup>, the constant used by [`montgomery_mul`] for a
+/// modulus whose low limb is `m0`.
+///
+/// Panics if `m0` is even.
+pub fn montgomery_inverse(m0: u64) -> u64 {
+    assert!(m0 & 1 == 1, "modulus must be odd");
+    // Newton's iteration doubles the number of correct low bits each step, starting from the 3
+    // bits that are correct because m0 * m0 = 1 modulo 8.
+    let mut inv = m0;
+    for _ in 0..5 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(m0.wrapping_mul(inv)));
+    }
+    debug_assert_eq!(m0.wrapping_mul(inv), 1);
+    inv.wrapping_neg()
+}
+
+/// Writes the Montgomery product `a * b / 2^(64n) mod m` to `r`, where `n` is the length of `m`.
+///
+/// `m` must be odd, `a` and `b` must be less than `m`, and `minv` must be
+/// [`montgomery_inverse`]`(m[0])`. All four slices must have the same, nonzero length. The
+/// result is fully reduced, i.e. less than `m`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_monty_parallel::limbs;
+///
+/// // With one limb, R = 2^64, so multiplying by R mod m gives back the other operand.
+/// let m = [1_000_000_007];
+/// let r_mod_m = [(1u128 << 64) % 1_000_000_007];
+/// let mut r = [0];
+/// limbs::montgomery_mul(&mut r, &[12345], &[r_mod_m[0] as u64], &m, limbs::montgomery_inverse(m[0]));
+/// assert_eq!(r, [12345]);
+/// ```
+pub fn montgomery_mul(r: &mut [u64], a: &[u64], b: &[u64], m: &[u64], minv: u64) {
+    let n = m.len();
+    assert!(n > 0 && r.len() == n && a.len() == n && b.len() == n);
+    debug_assert_eq!(m[0].wrapping_mul(minv), u64::MAX);
+    debug_assert!(cmp(a, m).is_lt() && cmp(b, m).is_lt());
+
+    // The same almost Montgomery product as `BigUint::modpow`, which is below 2m for operands
+    // below m, so one subtraction fully reduces it.
+    let z = kernels::montgomery(a, b, m, minv);
+    if cmp(&z, m).is_ge() {
+        sub_n(r, &z, m);
+    } else {
+        r.copy_from_slice(&z);
+    }
+}
+
+#[test]
+fn test_limbs() {
+    use num_integer::Integer;
+    use num_traits::One;
+
+    let mut rng = crate::biguint::TestRng::new(0x853c_49e6_748f_ea9b);
+    let mut number = |len| rng.words(len);
+    let big = |x: &[u64]| BigUint::from_u64_digits(x.to_vec());
+    let pow = |n: usize| BigUint::one() << (64 * n);
+
+    for &(la, lb) in &[(1, 1), (3, 2), (8, 8), (40, 17), (100, 100)] {
+        let (a, b) = (number(la), number(lb));
+
+        let mut r = vec![0; la];
+        let c = add_n(&mut r, &a, &a);
+        assert_eq!(big(&r) + (BigUint::from(c) << (64 * la)), big(&a) << 1);
+        let other = number(la);
+        let c = sub_n(&mut r, &a, &other);
+        let (x, y) = (big(&a), big(&other));
+        assert_eq!(big(&r), (&x + pow(la) - &y) % pow(la));
+        assert_eq!(c == 1, x < y);
+
+        let c = mul_1(&mut r, &a, b[0]);
+        assert_eq!(big(&r) + (BigUint::from(c) << (64 * la)), big(&a) * b[0]);
+        let before = big(&r);
+        let c = addmul_1(&mut r, &a, b[0]);
+        let sum = before + big(&a) * b[0];
+        assert_eq!(big(&r) + (BigUint::from(c) << (64 * la)), sum);
+        let current = big(&r);
+        let c = submul_1(&mut r, &a, b[0]);
+        assert_eq!(
+            big(&r),
+            current + (BigUint::from(c) << (64 * la)) - big(&a) * b[0]
+        );
+
+        let mut p = vec![0; la + lb];
+        mul(&mut p, &a, &b);
+        assert_eq!(big(&p), big(&a) * big(&b));
+        let mut p = vec![0; la * 2];
+        sqr(&mut p, &a);
+        assert_eq!(big(&p), big(&a) * big(&a));
+
+        for n in [1, lb, la + lb - 1, la + lb] {
+            let mut low = vec![0; n];
+            mul_low(&mut low, &a, &b);
+            assert_eq!(big(&low), big(&a) * big(&b) % pow(n));
+            let mut high = vec![0; la + lb - n];
+            mul_high(&mut high, &a, &b, n);
+            assert_eq!(big(&high), (big(&a) * big(&b)) >> (64 * n));
+        }
+
+        if la >= lb {
+            let (mut q, mut rem) = (vec![0; la - lb + 1], vec![0; lb]);
+            divrem(&mut q, &mut rem, &a, &b);
+            assert_eq!((big(&q), big(&rem)), big(&a).div_rem(&big(&b)));
+        }
+
+        assert_eq!(cmp(&a, &a), Ordering::Equal);
+        let mut s = vec![0; la];
+        for cnt in [0, 1, 13, 63] {
+            let c = lshift(&mut s, &a, cnt);
+            assert_eq!(big(&s) + (BigUint::from(c) << (64 * la)), big(&a) << cnt);
+            let c = rshift(&mut s, &a, cnt);
+            assert_eq!(big(&s), big(&a) >> cnt);
+            assert_eq!(
+                BigUint::from(c >> ((64 - cnt) % 64)),
+                big(&a) % (BigUint::one() << cnt)
+            );
+        }
+
+        let mut m = number(lb);
+        m[0] |= 1;
+        let (x, y) = (big(&a) % big(&m), big(&b) % big(&m));
+        let (mut xs, mut ys) = (vec![0; lb], vec![0; lb]);
+        write_digits(&mut xs, x.digits());
+        write_digits(&mut ys, y.digits());
+        let mut z = vec![0; lb];
+        montgomery_mul(&mut z, &xs, &ys, &m, montgomery_inverse(m[0]));
+        assert_eq!((big(&z) << (64 * lb)) % big(&m), x * y % big(&m));
+        assert!(cmp(&z, &m).is_lt());
+    }
+
+    // a single-limb divisor, a divisor whose top limb fits in 32 bits, and a dividend whose high
+    // zero limbs make it shorter than the divisor
+    for (a, d) in [
+        (&[5, 7, 9][..], &[3][..]),
+        (&[1, 2, 3, 4], &[u64::MAX, 1]),
+        (&[u64::MAX, u64::MAX, 0, 0], &[1, 2, 3]),
+        (&[0, 0, 0], &[0, 1 << 63]),
+    ] {
+        let (mut q, mut rem) = (vec![0; a.len() - d.len() + 1], vec![0; d.len()]);
+        divrem(&mut q, &mut rem, a, d);
+        assert_eq!((big(&q), big(&rem)), big(a).div_rem(&big(d)));
+    }
+}
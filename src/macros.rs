@@ -2,19 +2,19 @@
 
 macro_rules! cfg_32 {
     ($($any:tt)+) => {
-        #[cfg(not(target_pointer_width = "64"))] $($any)+
+        #[cfg(any(not(target_pointer_width = "64"), feature = "force-u32-digits"))] $($any)+
     }
 }
 
 macro_rules! cfg_32_or_test {
     ($($any:tt)+) => {
-        #[cfg(any(not(target_pointer_width = "64"), test))] $($any)+
+        #[cfg(any(not(target_pointer_width = "64"), feature = "force-u32-digits", test))] $($any)+
     }
 }
 
 macro_rules! cfg_64 {
     ($($any:tt)+) => {
-        #[cfg(target_pointer_width = "64")] $($any)+
+        #[cfg(all(target_pointer_width = "64", not(feature = "force-u32-digits")))] $($any)+
     }
 }
 
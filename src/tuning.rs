@@ -0,0 +1,270 @@
+//! Runtime-tunable algorithm thresholds.
+//!
+//! The arithmetic in this crate switches between several algorithms depending on the size of
+//! its operands, e.g. long multiplication for small inputs and Karatsuba or Toom-3 for larger
+//! ones. The best crossover points depend on the host CPU, so rather than being hardcoded they
+//! are collected in a [`Tuning`] profile that can be replaced at runtime.
+//!
+//! The bundled `autotune` binary benchmarks the host and prints a profile, either as text that
+//! can be read with [`Tuning::from_profile`], or as a Rust `const` to compile in directly:
+//!
+//! ```text
+//! cargo run --release --bin autotune > tuning.toml
+//! cargo run --release --bin autotune -- --rust > tuning.rs
+//! ```
+//!
+//! All thresholds are measured in `BigDigit`s, the internal digit size of [`BigUint`].
+//!
+//! [`BigUint`]: crate::BigUint
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+macro_rules! tuning_params {
+    ($($(#[$doc:meta])* $field:ident: $default:expr,)*) => {
+        /// A set of crossover points between the algorithms used for big integer arithmetic.
+        ///
+        /// The profile in effect is global to the process. It is read with [`Tuning::current`]
+        /// and replaced with [`Tuning::install`]. Every profile produces the same results, only
+        /// the performance differs.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct Tuning {
+            $($(#[$doc])* pub $field: usize,)*
+        }
+
+        struct AtomicTuning {
+            $($field: AtomicUsize,)*
+        }
+
+        static CURRENT: AtomicTuning = AtomicTuning {
+            $($field: AtomicUsize::new($default),)*
+        };
+
+        impl Tuning {
+            /// The built-in profile, used until another one is installed.
+            pub const DEFAULT: Tuning = Tuning {
+                $($field: $default,)*
+            };
+
+            /// Returns the profile currently in effect.
+            pub fn current() -> Tuning {
+                Tuning {
+                    $($field: CURRENT.$field.load(Relaxed),)*
+                }
+            }
+
+            /// Makes this profile the one in effect for all subsequent operations.
+            ///
+            /// Panics if the profile is not valid, see [`Tuning::validate`].
+            pub fn install(self) {
+                if let Err(e) = self.validate() {
+                    panic!("invalid tuning profile: {}", e);
+                }
+                $(CURRENT.$field.store(self.$field, Relaxed);)*
+            }
+
+            fn set(&mut self, key: &str, value: usize) -> Result<(), ParseTuningError> {
+                match key {
+                    $(stringify!($field) => self.$field = value,)*
+                    _ => return Err(ParseTuningError::unknown_key()),
+                }
+                Ok(())
+            }
+
+            /// Writes the profile in the format read by [`Tuning::from_profile`].
+            pub fn to_profile(&self) -> String {
+                let mut s = String::from("[tuning]\n");
+                $(writeln!(s, "{} = {}", stringify!($field), self.$field).unwrap();)*
+                s
+            }
+        }
+
+        $(
+            #[inline]
+            pub(crate) fn $field() -> usize {
+                CURRENT.$field.load(Relaxed)
+            }
+        )*
+    };
+}
+
+tuning_params! {
+    /// Multiplication operands longer than this use Karatsuba instead of long multiplication.
+    /// Must be at least 1.
+    mul_karatsuba_threshold: 32,
+    /// Multiplication operands longer than this use Toom-3 instead of Karatsuba.
+    mul_toom3_threshold: 256,
+    /// Multiplication operands of similar length longer than this use Toom-4 instead of Toom-3.
+    mul_toom4_threshold: 8192,
+    /// Multiplication operands longer than this use number-theoretic transforms instead of
+    /// Toom-Cook.
+    mul_ntt_threshold: 12_288,
+    /// Divisions whose divisor and quotient are both longer than this use Burnikel–Ziegler
+    /// recursive division instead of long division. Must be at least 1.
+    div_burnikel_ziegler_threshold: 64,
+    /// Divisions whose divisor and quotient are both longer than this use Newton's iteration for
+    /// the reciprocal of the divisor instead of Burnikel–Ziegler division.
+    div_newton_threshold: 262_144,
+    /// Numbers at least this long are split by a power of the radix before being converted to a
+    /// non-power-of-two radix.
+    radix_divide_threshold: 64,
+    /// The number of exponent bits consumed per step of Montgomery exponentiation.
+    /// Must be 1, 2, 4 or 8.
+    monty_window_bits: 4,
+    /// Work is only split across the threads of the [executor](crate::executor) in pieces of
+    /// at least this many digits. Must be at least 1.
+    parallel_threshold: 1024,
+}
+
+impl Default for Tuning {
+    #[inline]
+    fn default() -> Tuning {
+        Tuning::DEFAULT
+    }
+}
+
+impl Tuning {
+    /// Checks that the thresholds are usable, returning an error otherwise.
+    pub fn validate(&self) -> Result<(), ParseTuningError> {
+        if self.mul_karatsuba_threshold == 0
+            || self.div_burnikel_ziegler_threshold == 0
+            || self.parallel_threshold == 0
+            || !matches!(self.monty_window_bits, 1 | 2 | 4 | 8)
+        {
+            return Err(ParseTuningError::out_of_range());
+        }
+        Ok(())
+    }
+
+    /// Reads a profile as written by [`Tuning::to_profile`] or the `autotune` binary.
+    ///
+    /// The format is a subset of TOML: one `name = value` pair per line, with `#` comments and
+    /// `[section]` headers ignored. Thresholds not mentioned keep their default value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_monty_parallel::Tuning;
+    ///
+    /// let profile = "[tuning]\nmul_karatsuba_threshold = 40 # measured\n";
+    /// let tuning = Tuning::from_profile(profile).unwrap();
+    /// assert_eq!(tuning.mul_karatsuba_threshold, 40);
+    /// assert_eq!(tuning.mul_toom3_threshold, Tuning::DEFAULT.mul_toom3_threshold);
+    /// ```
+    pub fn from_profile(profile: &str) -> Result<Tuning, ParseTuningError> {
+        let mut tuning = Tuning::DEFAULT;
+        for line in profile.lines() {
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(ParseTuningError::syntax)?;
+            let value: String = value.trim().chars().filter(|&c| c != '_').collect();
+            let value = value.parse().map_err(|_| ParseTuningError::syntax())?;
+            tuning.set(key.trim(), value)?;
+        }
+        tuning.validate()?;
+        Ok(tuning)
+    }
+}
+
+/// The error type returned when a tuning profile cannot be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTuningError {
+    kind: TuningErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TuningErrorKind {
+    Syntax,
+    UnknownKey,
+    OutOfRange,
+}
+
+impl ParseTuningError {
+    fn __description(&self) -> &str {
+        use self::TuningErrorKind::*;
+        match self.kind {
+            Syntax => "expected a `name = integer` line in tuning profile",
+            UnknownKey => "unknown threshold name in tuning profile",
+            OutOfRange => "threshold out of range in tuning profile",
+        }
+    }
+
+    fn syntax() -> Self {
+        ParseTuningError {
+            kind: TuningErrorKind::Syntax,
+        }
+    }
+
+    fn unknown_key() -> Self {
+        ParseTuningError {
+            kind: TuningErrorKind::UnknownKey,
+        }
+    }
+
+    fn out_of_range() -> Self {
+        ParseTuningError {
+            kind: TuningErrorKind::OutOfRange,
+        }
+    }
+}
+
+impl fmt::Display for ParseTuningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.__description().fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for ParseTuningError {
+    fn description(&self) -> &str {
+        self.__description()
+    }
+}
+
+#[test]
+fn test_profile_roundtrip() {
+    let tuning = Tuning {
+        mul_karatsuba_threshold: 24,
+        mul_toom3_threshold: 300,
+        mul_toom4_threshold: 2_000,
+        mul_ntt_threshold: 5_000,
+        div_burnikel_ziegler_threshold: 50,
+        div_newton_threshold: 6_000,
+        radix_divide_threshold: 1_000,
+        monty_window_bits: 2,
+        parallel_threshold: 1,
+    };
+    assert_eq!(Tuning::from_profile(&tuning.to_profile()), Ok(tuning));
+}
+
+#[test]
+fn test_profile_errors() {
+    assert_eq!(
+        Tuning::from_profile("mul_karatsuba_threshold 32"),
+        Err(ParseTuningError::syntax())
+    );
+    assert_eq!(
+        Tuning::from_profile("mul_karatsuba_threshold = -1"),
+        Err(ParseTuningError::syntax())
+    );
+    assert_eq!(
+        Tuning::from_profile("karatsuba = 32"),
+        Err(ParseTuningError::unknown_key())
+    );
+    assert_eq!(
+        Tuning::from_profile("monty_window_bits = 3"),
+        Err(ParseTuningError::out_of_range())
+    );
+    assert_eq!(
+        Tuning::from_profile("div_burnikel_ziegler_threshold = 0"),
+        Err(ParseTuningError::out_of_range())
+    );
+}
@@ -0,0 +1,69 @@
+use num_traits::Num;
+use rust_monty_parallel::executor::{self, Executor, Serial};
+use rust_monty_parallel::{BigUint, Tuning};
+
+/// Runs tasks in reverse order, while claiming to be parallel so that work gets split.
+struct Reversed;
+
+impl Executor for Reversed {
+    fn scope(&self, tasks: &mut [&mut (dyn FnMut() + Send)]) {
+        for task in tasks.iter_mut().rev() {
+            task();
+        }
+    }
+
+    fn current_num_threads(&self) -> usize {
+        4
+    }
+}
+
+// The executor and tuning profile are global, so everything that installs one lives in a
+// single test.
+#[test]
+fn test_executors_agree() {
+    let b = BigUint::from_str_radix(&"123456789abcdef0".repeat(40), 16).unwrap();
+    let e = BigUint::from_str_radix("fedcba9876543210", 16).unwrap();
+    let m = BigUint::from_str_radix(&"f1e2d3c4b5a69787".repeat(48), 16).unwrap();
+
+    Tuning {
+        parallel_threshold: 4,
+        ..Tuning::DEFAULT
+    }
+    .install();
+
+    // Random primes take the same draws from a seeded generator on any executor.
+    #[cfg(feature = "rand")]
+    let primes = || {
+        use rand::SeedableRng;
+        use rust_monty_parallel::RandPrime;
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(1);
+        [
+            rng.gen_prime(256),
+            rng.gen_safe_prime(160),
+            rng.gen_prime(256),
+        ]
+    };
+    #[cfg(not(feature = "rand"))]
+    let primes = || ();
+
+    let results = || (b.modpow(&e, &m), BigUint::factorial(3000), primes());
+
+    executor::set_executor(&Serial);
+    let expected = results();
+
+    executor::set_executor(&Reversed);
+    assert!(results() == expected);
+
+    #[cfg(feature = "std")]
+    {
+        executor::set_executor(&executor::StdThreads);
+        assert!(results() == expected);
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        executor::set_executor(&executor::Rayon);
+        assert!(results() == expected);
+    }
+}
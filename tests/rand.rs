@@ -0,0 +1,240 @@
+#![cfg(feature = "rand")]
+
+use rand::distributions::Uniform;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_xorshift::XorShiftRng;
+use rust_monty_parallel::{BigInt, BigUint, RandBigInt, RandPrime, RandomBits};
+
+use num_traits::{One, Signed, Zero};
+
+fn rng() -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(0x5eed)
+}
+
+mod biguint {
+    use super::*;
+
+    #[test]
+    fn test_rand() {
+        let mut rng = rng();
+        for bits in [0u64, 1, 31, 32, 33, 63, 64, 65, 137, 1000] {
+            for _ in 0..10 {
+                let n = rng.gen_biguint(bits);
+                assert!(n.bits() <= bits);
+            }
+            let n: BigUint = rng.sample(RandomBits::new(bits));
+            assert!(n.bits() <= bits);
+        }
+        // with this many draws, the top bit is set in some of them
+        assert!((0..32).any(|_| rng.gen_biguint(137).bits() == 137));
+    }
+
+    #[test]
+    fn test_rand_range() {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+
+        for _ in 0..10 {
+            assert_eq!(
+                rng.gen_biguint_range(&BigUint::from(236u32), &BigUint::from(237u32)),
+                BigUint::from(236u32)
+            );
+        }
+
+        let l = BigUint::from(403_469_000u32 + 2352);
+        let u = BigUint::from(403_469_000u32 + 3513);
+        for _ in 0..1000 {
+            let n = rng.gen_biguint_below(&u);
+            assert!(n < u);
+
+            let n = rng.gen_biguint_range(&l, &u);
+            assert!(n >= l);
+            assert!(n < u);
+        }
+
+        // a power of two bound, which is drawn without rejection
+        let bound = BigUint::one() << 100u8;
+        assert!((0..100).all(|_| rng.gen_biguint_below(&bound) < bound));
+        assert!(BigUint::one() >= rng.gen_biguint_below(&BigUint::one()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_rand_range() {
+        rng().gen_biguint_range(&BigUint::from(54u32), &BigUint::from(54u32));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_rand_range() {
+        let l = BigUint::from(2352u32);
+        let u = BigUint::from(3513u32);
+        // Switching u and l should fail:
+        rng().gen_biguint_range(&u, &l);
+    }
+
+    #[test]
+    fn test_rand_uniform() {
+        let mut rng = rng();
+
+        let tiny = Uniform::new(BigUint::from(236u32), BigUint::from(237u32));
+        for _ in 0..10 {
+            assert_eq!(rng.sample(&tiny), BigUint::from(236u32));
+        }
+
+        let l = BigUint::from(403_469_000u32 + 2352);
+        let u = BigUint::from(403_469_000u32 + 3513);
+        let below = Uniform::new(BigUint::zero(), u.clone());
+        let range = Uniform::new(l.clone(), u.clone());
+        let inclusive = Uniform::new_inclusive(l.clone(), u.clone());
+        for _ in 0..1000 {
+            assert!(rng.sample(&below) < u);
+            let n = rng.sample(&range);
+            assert!(n >= l && n < u);
+            let n = rng.sample(&inclusive);
+            assert!(n >= l && n <= u);
+        }
+
+        // every value of a small range turns up
+        let mut seen = [false; 10];
+        let digits = Uniform::new(BigUint::from(10u32), BigUint::from(20u32));
+        for _ in 0..200 {
+            let n: BigUint = rng.sample(&digits) - 10u32;
+            seen[n.to_u32_digits().first().copied().unwrap_or(0) as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+
+    #[test]
+    fn seeded_value_stability() {
+        // The draws only depend on the generator's u32 stream, so these hold for both 32- and
+        // 64-bit digits.
+        let mut rng = rng();
+        let bound = BigUint::parse_bytes(b"1000000000000000000000007", 10).unwrap();
+        let expected = [
+            "0",
+            "0",
+            "2580571611",
+            "497028142087704148114083939256",
+            "935805340990658100086361",
+        ];
+        let got = [
+            rng.gen_biguint(0),
+            rng.gen_biguint(1),
+            rng.gen_biguint(32),
+            rng.gen_biguint(100),
+            rng.gen_biguint_below(&bound),
+        ];
+        for (got, expected) in got.iter().zip(expected) {
+            assert_eq!(got.to_string(), expected);
+        }
+
+        let mut rng = XorShiftRng::seed_from_u64(7);
+        assert_eq!(
+            rng.gen_prime(96).to_string(),
+            "57252645166991855948388423287"
+        );
+    }
+}
+
+mod bigint {
+    use super::*;
+
+    #[test]
+    fn test_rand() {
+        let mut rng = rng();
+        let mut signs = [false; 2];
+        for bits in [0u64, 1, 32, 64, 65, 137, 1000] {
+            for _ in 0..10 {
+                let n = rng.gen_bigint(bits);
+                assert!(n.bits() <= bits);
+                if !n.is_zero() {
+                    signs[usize::from(n.is_positive())] = true;
+                }
+            }
+            let n: BigInt = rng.sample(RandomBits::new(bits));
+            assert!(n.bits() <= bits);
+        }
+        assert_eq!(signs, [true, true]);
+    }
+
+    #[test]
+    fn test_rand_range() {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+
+        for _ in 0..10 {
+            assert_eq!(
+                rng.gen_bigint_range(&BigInt::from(236), &BigInt::from(237)),
+                BigInt::from(236)
+            );
+        }
+
+        fn check(l: BigInt, u: BigInt) {
+            let mut rng = super::rng();
+            for _ in 1..1000 {
+                let n: BigInt = rng.gen_bigint_range(&l, &u);
+                assert!(n >= l);
+                assert!(n < u);
+            }
+        }
+        let l: BigInt = BigInt::from(403_469_000 + 2352);
+        let u: BigInt = BigInt::from(403_469_000 + 3513);
+        check(l.clone(), u.clone());
+        check(-l.clone(), u.clone());
+        check(-u, -l);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_rand_range() {
+        rng().gen_bigint_range(&BigInt::from(-54), &BigInt::from(-54));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_rand_range() {
+        let l = BigInt::from(2352);
+        let u = BigInt::from(3513);
+        // Switching u and l should fail:
+        rng().gen_bigint_range(&u, &l);
+    }
+
+    #[test]
+    fn test_rand_uniform() {
+        let mut rng = rng();
+
+        let tiny = Uniform::new(BigInt::from(-237), BigInt::from(-236));
+        for _ in 0..10 {
+            assert_eq!(rng.sample(&tiny), BigInt::from(-237));
+        }
+
+        let l = BigInt::from(-403_469_000 - 2352);
+        let u = BigInt::from(403_469_000 + 3513);
+        let range = Uniform::new(l.clone(), u.clone());
+        let inclusive = Uniform::new_inclusive(l.clone(), u.clone());
+        for _ in 0..1000 {
+            let n = rng.sample(&range);
+            assert!(n >= l && n < u);
+            let n = rng.sample(&inclusive);
+            assert!(n >= l && n <= u);
+        }
+    }
+
+    #[test]
+    fn seeded_value_stability() {
+        let mut rng = rng();
+        let got = [
+            rng.gen_bigint(64),
+            rng.gen_bigint(100),
+            rng.gen_bigint_range(&BigInt::from(-1_000_000), &BigInt::from(1_000_000)),
+        ];
+        let expected = [
+            "-11083470674513879611",
+            "-1062543011774743243078255607302",
+            "623376",
+        ];
+        for (got, expected) in got.iter().zip(expected) {
+            assert_eq!(got.to_string(), expected);
+        }
+    }
+}
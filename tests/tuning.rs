@@ -0,0 +1,62 @@
+use num_traits::Num;
+use rust_monty_parallel::{BigUint, Tuning};
+
+// The tuning profile is global, so everything that installs one lives in a single test.
+#[test]
+fn test_profiles_agree() {
+    let a = BigUint::from_str_radix(&"f1e2d3c4b5a69788".repeat(300), 16).unwrap();
+    let b = BigUint::from_str_radix(&"123456789abcdef0".repeat(170), 16).unwrap();
+    let m = BigUint::from_str_radix(&"fedcba9876543211".repeat(32), 16).unwrap();
+
+    let results = |tuning: Tuning| {
+        tuning.install();
+        (
+            &a * &b,
+            &a * &a,
+            a.square(),
+            a.to_str_radix(10),
+            b.modpow(&a, &m),
+            &a * &a / &b,
+            &a % &b,
+        )
+    };
+
+    let expected = results(Tuning::DEFAULT);
+    assert_eq!(expected.1, expected.2);
+    let small = Tuning {
+        mul_karatsuba_threshold: 1,
+        mul_toom3_threshold: 4,
+        mul_toom4_threshold: 6,
+        mul_ntt_threshold: 8,
+        div_burnikel_ziegler_threshold: 1,
+        div_newton_threshold: 2,
+        radix_divide_threshold: 2,
+        monty_window_bits: 1,
+        parallel_threshold: 1,
+    };
+    let large = Tuning {
+        mul_karatsuba_threshold: usize::MAX,
+        mul_ntt_threshold: usize::MAX,
+        div_burnikel_ziegler_threshold: usize::MAX,
+        div_newton_threshold: usize::MAX,
+        radix_divide_threshold: usize::MAX,
+        monty_window_bits: 8,
+        ..Tuning::DEFAULT
+    };
+    let toom = Tuning {
+        mul_karatsuba_threshold: 2,
+        mul_toom3_threshold: 4,
+        mul_toom4_threshold: 8,
+        mul_ntt_threshold: usize::MAX,
+        ..Tuning::DEFAULT
+    };
+    let burnikel_ziegler = Tuning {
+        div_burnikel_ziegler_threshold: 2,
+        ..Tuning::DEFAULT
+    };
+    assert!(expected == results(small));
+    assert!(expected == results(burnikel_ziegler));
+    assert!(expected == results(toom));
+    assert!(expected == results(large));
+    Tuning::DEFAULT.install();
+}